@@ -0,0 +1,124 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Dynamically typed errors.
+
+use core::error::Error;
+
+use crate::AnyDebug;
+
+/// An error which can be passed around as a dynamically typed value.
+///
+/// This trait is implemented for all `'static` types which implement [`Error`].
+/// `dyn AnyDebugError` (and its combinations with `Send` and `Sync`) implement `Error`,
+/// so the [`source`](Error::source) chain of the error can be followed, and also have the
+/// same downcasting methods as `dyn AnyDebug`.
+///
+/// ## Examples
+///
+/// ```
+/// use core::fmt;
+/// use anymore::AnyDebugError;
+///
+/// #[derive(Debug)]
+/// struct SaveFailed;
+///
+/// impl fmt::Display for SaveFailed {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("the document could not be saved")
+///     }
+/// }
+///
+/// impl core::error::Error for SaveFailed {}
+///
+/// let error: &(dyn AnyDebugError + Send + Sync) = &SaveFailed;
+/// assert_eq!(error.to_string(), "the document could not be saved");
+/// assert!(error.source().is_none());
+/// assert!(error.is::<SaveFailed>());
+/// ```
+pub trait AnyDebugError: AnyDebug + Error {}
+
+impl<T: AnyDebug + Error> AnyDebugError for T {}
+
+crate::impl_downcast!(dyn AnyDebugError);
+crate::impl_downcast!(dyn AnyDebugError + Send);
+crate::impl_downcast!(dyn AnyDebugError + Sync);
+crate::impl_downcast!(dyn AnyDebugError + Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::AnyDebugError;
+    #[cfg(feature = "alloc")]
+    use crate::AnyDebug;
+    #[cfg(feature = "alloc")]
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use core::error::Error;
+    use core::fmt;
+
+    #[derive(Debug)]
+    struct Inner(u32);
+
+    impl fmt::Display for Inner {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "inner error {}", self.0)
+        }
+    }
+
+    impl Error for Inner {}
+
+    #[derive(Debug)]
+    struct Outer(Inner);
+
+    impl fmt::Display for Outer {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("outer error")
+        }
+    }
+
+    impl Error for Outer {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn any_debug_error_source() {
+        let val: &dyn AnyDebugError = &Outer(Inner(140));
+        assert_eq!(val.to_string(), "outer error");
+        let source = val.source().unwrap();
+        assert_eq!(source.downcast_ref::<Inner>().unwrap().0, 140);
+    }
+    #[test]
+    fn any_debug_error_downcast_ref() {
+        let val: &(dyn AnyDebugError + Sync) = &Inner(141);
+        assert!(!val.is::<Outer>());
+        assert_eq!(val.expect_downcast_ref::<Inner>().0, 141);
+    }
+    #[test]
+    fn any_debug_error_downcast_mut() {
+        let mut val = Inner(142);
+        let val_mut: &mut (dyn AnyDebugError + Send) = &mut val;
+        val_mut.downcast_mut::<Inner>().unwrap().0 = 143;
+        assert!(val_mut.downcast_mut::<Outer>().is_none());
+        assert_eq!(val.0, 143);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_error_downcast() {
+        let val: Box<dyn AnyDebugError + Send + Sync> = Box::new(Outer(Inner(144)));
+        let val = val.downcast::<Inner>().unwrap_err();
+        assert_eq!(val.downcast::<Outer>().unwrap().0 .0, 144);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_error_is_nested_box() {
+        let val: Box<dyn AnyDebugError> = Box::new(Inner(145));
+        assert!(!val.is_nested_box());
+        // `Box<dyn Error>` isn't itself an `Error`, so can only be erased again as `AnyDebug`.
+        let val: Box<dyn AnyDebug> =
+            Box::new(Box::new(Inner(146)) as Box<dyn AnyDebugError + Send + Sync>);
+        assert!(val.is_nested_box());
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Short lists of dynamically typed arguments.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use crate::AnyDebug;
+
+/// The number of arguments which are stored without allocating a `Vec`.
+const ARRAY_LEN: usize = 4;
+
+/// A list of dynamically typed arguments, such as those passed to a command.
+///
+/// Up to four arguments are stored without a `Vec`, so building a short argument list only
+/// allocates the arguments themselves, which are always boxed.
+/// Arguments can be accessed by their position, using [`get`](AnyArgs::get) and
+/// [`get_as`](AnyArgs::get_as), or by their type, using [`find`](AnyArgs::find).
+///
+/// The `Debug` output lists the `Debug` output of every argument.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyArgs;
+///
+/// let args = AnyArgs::new().with("open").with(3_u32);
+/// assert_eq!(args.get_as::<&str>(0), Some(&"open"));
+/// assert_eq!(args.find::<u32>(), Some(&3));
+/// assert_eq!(format!("{args:?}"), r#"["open", 3]"#);
+/// ```
+#[derive(Default)]
+pub struct AnyArgs {
+    storage: Storage,
+}
+
+enum Storage {
+    Array {
+        args: [Option<Box<dyn AnyDebug>>; ARRAY_LEN],
+        len: usize,
+    },
+    Heap(Vec<Box<dyn AnyDebug>>),
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self::Array {
+            args: Default::default(),
+            len: 0,
+        }
+    }
+}
+
+impl AnyArgs {
+    /// Creates an empty argument list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `value` to the end of the argument list.
+    pub fn push<T: AnyDebug>(&mut self, value: T) {
+        self.push_boxed(Box::new(value));
+    }
+
+    /// Adds the already boxed `value` to the end of the argument list.
+    pub fn push_boxed(&mut self, value: Box<dyn AnyDebug>) {
+        match &mut self.storage {
+            Storage::Array { args, len } if *len < ARRAY_LEN => {
+                args[*len] = Some(value);
+                *len += 1;
+            }
+            Storage::Array { args, .. } => {
+                let mut heap: Vec<_> = args.iter_mut().filter_map(Option::take).collect();
+                heap.push(value);
+                self.storage = Storage::Heap(heap);
+            }
+            Storage::Heap(args) => args.push(value),
+        }
+    }
+
+    /// Returns the argument list with `value` added to the end.
+    pub fn with<T: AnyDebug>(mut self, value: T) -> Self {
+        self.push(value);
+        self
+    }
+
+    /// Returns the number of arguments.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Array { len, .. } => *len,
+            Storage::Heap(args) => args.len(),
+        }
+    }
+
+    /// Returns `true` if there are no arguments.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the argument at `index`, or `None` if there are not enough arguments.
+    pub fn get(&self, index: usize) -> Option<&dyn AnyDebug> {
+        match &self.storage {
+            Storage::Array { args, .. } => args.get(index)?.as_deref(),
+            Storage::Heap(args) => args.get(index).map(|arg| &**arg),
+        }
+    }
+
+    /// Returns the argument at `index` if it is of type `T`, or `None` if it isn't or if there
+    /// are not enough arguments.
+    pub fn get_as<T: AnyDebug>(&self, index: usize) -> Option<&T> {
+        self.get(index)?.downcast_ref()
+    }
+
+    /// Returns the first argument of type `T`, or `None` if there isn't one.
+    pub fn find<T: AnyDebug>(&self) -> Option<&T> {
+        self.iter().find_map(|arg| arg.downcast_ref())
+    }
+
+    /// Returns an iterator over the arguments.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn AnyDebug> {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+}
+
+impl Debug for AnyArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnyArgs;
+    use alloc::{boxed::Box, format};
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn any_args_get() {
+        let args = AnyArgs::new().with(SomeMessage(190)).with(191_u8);
+        assert_eq!(args.len(), 2);
+        assert_eq!(args.get_as::<SomeMessage>(0).unwrap().0, 190);
+        assert!(args.get_as::<SomeMessage>(1).is_none());
+        assert!(args.get(2).is_none());
+        assert_eq!(args.find::<u8>(), Some(&191));
+        assert!(args.find::<u16>().is_none());
+    }
+    #[test]
+    fn any_args_spill() {
+        let mut args = AnyArgs::new();
+        for i in 0..6_u32 {
+            args.push(SomeMessage(192 + i));
+        }
+        args.push_boxed(Box::new(198_u32));
+        assert_eq!(args.len(), 7);
+        assert_eq!(args.get_as::<SomeMessage>(4).unwrap().0, 196);
+        assert_eq!(args.find::<u32>(), Some(&198));
+        assert_eq!(args.iter().count(), 7);
+    }
+    #[test]
+    fn any_args_fifth_argument() {
+        let mut args = AnyArgs::new();
+        for i in 0..4_u32 {
+            args.push(SomeMessage(900 + i));
+        }
+        args.push(904_u16);
+        assert_eq!(args.len(), 5);
+        for (index, value) in (900..904_u32).enumerate() {
+            assert_eq!(args.get_as::<SomeMessage>(index).unwrap().0, value);
+        }
+        assert_eq!(args.get_as::<u16>(4), Some(&904));
+        assert!(args.get(5).is_none());
+        assert_eq!(args.find::<SomeMessage>().unwrap().0, 900);
+        assert_eq!(args.find::<u16>(), Some(&904));
+    }
+    #[test]
+    fn any_args_debug() {
+        let args = AnyArgs::new().with(SomeMessage(199)).with("text");
+        assert_eq!(format!("{args:?}"), r#"[SomeMessage(199), "text"]"#);
+        assert!(AnyArgs::new().is_empty());
+        assert_eq!(format!("{:?}", AnyArgs::new()), "[]");
+    }
+}
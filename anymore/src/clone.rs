@@ -0,0 +1,150 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Dynamically typed values which can be cloned.
+
+use alloc::boxed::Box;
+
+use crate::AnyDebug;
+
+/// An [`AnyDebug`] value which can be cloned into a new box.
+///
+/// This trait is implemented for all types which implement [`AnyDebug`] and [`Clone`].
+/// `Box<dyn AnyDebugClone>` (and its combinations with `Send` and `Sync`) implement `Clone`,
+/// which is useful for delivering the same dynamically typed value to several recipients.
+/// `dyn AnyDebugClone` also has the same downcasting methods as `dyn AnyDebug`.
+///
+/// Note that as `Box<dyn AnyDebugClone>` is itself `Clone`, calling `clone_box` directly on
+/// the box (rather than on the value it contains) will create a nested box.
+/// Use `Box::clone` (or the `clone` method) instead.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyDebugClone;
+///
+/// #[derive(Debug, Clone)]
+/// struct Click { x: f64 }
+///
+/// let message: Box<dyn AnyDebugClone + Send> = Box::new(Click { x: 1.5 });
+/// let copy = message.clone();
+/// assert_eq!(copy.downcast_ref::<Click>().unwrap().x, 1.5);
+/// ```
+pub trait AnyDebugClone: AnyDebug {
+    /// Clones this value into a new `Box<dyn AnyDebugClone>`.
+    fn clone_box(&self) -> Box<dyn AnyDebugClone>;
+
+    /// Clones this value into a new `Box<dyn AnyDebugClone + Send>`.
+    fn clone_box_send(&self) -> Box<dyn AnyDebugClone + Send>
+    where
+        Self: Send;
+
+    /// Clones this value into a new `Box<dyn AnyDebugClone + Sync>`.
+    fn clone_box_sync(&self) -> Box<dyn AnyDebugClone + Sync>
+    where
+        Self: Sync;
+
+    /// Clones this value into a new `Box<dyn AnyDebugClone + Send + Sync>`.
+    fn clone_box_send_sync(&self) -> Box<dyn AnyDebugClone + Send + Sync>
+    where
+        Self: Send + Sync;
+}
+
+impl<T: AnyDebug + Clone> AnyDebugClone for T {
+    fn clone_box(&self) -> Box<dyn AnyDebugClone> {
+        Box::new(self.clone())
+    }
+
+    fn clone_box_send(&self) -> Box<dyn AnyDebugClone + Send>
+    where
+        Self: Send,
+    {
+        Box::new(self.clone())
+    }
+
+    fn clone_box_sync(&self) -> Box<dyn AnyDebugClone + Sync>
+    where
+        Self: Sync,
+    {
+        Box::new(self.clone())
+    }
+
+    fn clone_box_send_sync(&self) -> Box<dyn AnyDebugClone + Send + Sync>
+    where
+        Self: Send + Sync,
+    {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn AnyDebugClone> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+impl Clone for Box<dyn AnyDebugClone + Send> {
+    fn clone(&self) -> Self {
+        (**self).clone_box_send()
+    }
+}
+
+impl Clone for Box<dyn AnyDebugClone + Sync> {
+    fn clone(&self) -> Self {
+        (**self).clone_box_sync()
+    }
+}
+
+impl Clone for Box<dyn AnyDebugClone + Send + Sync> {
+    fn clone(&self) -> Self {
+        (**self).clone_box_send_sync()
+    }
+}
+
+crate::impl_downcast!(dyn AnyDebugClone);
+crate::impl_downcast!(dyn AnyDebugClone + Send);
+crate::impl_downcast!(dyn AnyDebugClone + Sync);
+crate::impl_downcast!(dyn AnyDebugClone + Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    use super::AnyDebugClone;
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Clone)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn clone_box_normal() {
+        let val: Box<dyn AnyDebugClone> = Box::new(SomeMessage(110));
+        let copy = val.clone();
+        assert_eq!(copy.downcast_ref::<SomeMessage>().unwrap().0, 110);
+        assert!(!copy.is_nested_box());
+    }
+    #[test]
+    fn clone_box_send() {
+        let val: Box<dyn AnyDebugClone + Send> = Box::new(SomeMessage(111));
+        let copy = val.clone();
+        assert_eq!(copy.downcast::<SomeMessage>().unwrap().0, 111);
+    }
+    #[test]
+    fn clone_box_sync() {
+        let val: Box<dyn AnyDebugClone + Sync> = Box::new(SomeMessage(112));
+        let copy = val.clone();
+        assert_eq!(copy.downcast::<SomeMessage>().unwrap().0, 112);
+    }
+    #[test]
+    fn clone_box_send_sync() {
+        let val: Box<dyn AnyDebugClone + Send + Sync> = Box::new(SomeMessage(113));
+        let copies: Vec<_> = (0..3).map(|_| val.clone()).collect();
+        for copy in copies {
+            assert_eq!(copy.downcast::<SomeMessage>().unwrap().0, 113);
+        }
+    }
+    #[test]
+    fn clone_box_on_box_is_nested() {
+        let val: Box<dyn AnyDebugClone> = Box::new(SomeMessage(114));
+        assert!(val.clone_box().is_nested_box());
+    }
+}
@@ -0,0 +1,444 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Comparison and hashing of dynamically typed values.
+
+use core::any::TypeId;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+use crate::AnyDebug;
+
+/// An [`AnyDebug`] value which can be compared for equality with other dynamically typed values.
+///
+/// This trait is implemented for all types which implement [`AnyDebug`] and [`PartialEq`].
+/// Two values are equal if they are of the same type, and equal according to that type's
+/// `PartialEq` implementation.
+///
+/// `dyn DynPartialEq` (and its combinations with `Send` and `Sync`) implement `PartialEq`,
+/// so can be compared using `==` and `assert_eq!`.
+/// Boxes of them also implement `PartialEq`, so can be used in collections and with
+/// methods such as `Vec::dedup`.
+/// However, using `==` on two boxes fails to compile because of a
+/// [compiler limitation](https://github.com/rust-lang/rust/issues/31740), so compare their
+/// contents instead, using `*a == *b` or `assert_eq!(*a, *b)`.
+/// They also have the same downcasting methods as `dyn AnyDebug`.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::DynPartialEq;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Scroll(i32);
+///
+/// let a: &dyn DynPartialEq = &Scroll(3);
+/// let b: &dyn DynPartialEq = &Scroll(3);
+/// let c: &dyn DynPartialEq = &3_i32;
+/// assert_eq!(a, b);
+/// assert_ne!(a, c);
+///
+/// let a: Box<dyn DynPartialEq> = Box::new(Scroll(3));
+/// let b: Box<dyn DynPartialEq> = Box::new(Scroll(3));
+/// assert_eq!(*a, *b);
+/// ```
+pub trait DynPartialEq: AnyDebug {
+    /// Returns `true` if `other` is of the same type as `self`, and is equal to it.
+    fn dyn_eq(&self, other: &dyn AnyDebug) -> bool;
+}
+
+impl<T: AnyDebug + PartialEq> DynPartialEq for T {
+    fn dyn_eq(&self, other: &dyn AnyDebug) -> bool {
+        other.downcast_ref::<T>().is_some_and(|other| self == other)
+    }
+}
+
+macro_rules! impl_partial_eq {
+    ($($ty:ty),+) => {
+        $(
+            impl PartialEq for $ty {
+                fn eq(&self, other: &Self) -> bool {
+                    self.dyn_eq(other)
+                }
+            }
+        )+
+    };
+}
+
+impl_partial_eq!(
+    dyn DynPartialEq,
+    dyn DynPartialEq + Send,
+    dyn DynPartialEq + Sync,
+    dyn DynPartialEq + Send + Sync
+);
+
+/// An [`AnyDebug`] value which can be hashed, and compared for equality with other
+/// dynamically typed values.
+///
+/// This trait is implemented for all types which implement [`AnyDebug`], [`Eq`] and [`Hash`].
+/// The hash of a value combines the [`TypeId`] of its type with the value's own hash, so
+/// equal values of different types are unlikely to collide.
+///
+/// `dyn DynHash` (and its combinations with `Send` and `Sync`) implement `Hash` and `Eq`,
+/// so boxed values can be used as keys in a `HashMap` or `HashSet`.
+/// They also have the same downcasting methods as `dyn AnyDebug`.
+///
+/// ## Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use anymore::DynHash;
+///
+/// #[derive(Debug, PartialEq, Eq, Hash)]
+/// struct Command(&'static str);
+///
+/// let mut cache: HashMap<Box<dyn DynHash>, u32> = HashMap::new();
+/// cache.insert(Box::new(Command("open")), 1);
+/// cache.insert(Box::new(7_u8), 2);
+/// assert_eq!(cache[&(Box::new(Command("open")) as Box<dyn DynHash>)], 1);
+/// ```
+pub trait DynHash: DynPartialEq {
+    /// Feeds this value, including its type, into the given [`Hasher`].
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+}
+
+impl<T: AnyDebug + Eq + Hash> DynHash for T {
+    fn dyn_hash(&self, mut state: &mut dyn Hasher) {
+        TypeId::of::<T>().hash(&mut state);
+        self.hash(&mut state);
+    }
+}
+
+macro_rules! impl_hash {
+    ($($ty:ty),+) => {
+        $(
+            impl Hash for $ty {
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    self.dyn_hash(state);
+                }
+            }
+
+            impl Eq for $ty {}
+        )+
+    };
+}
+
+impl_partial_eq!(
+    dyn DynHash,
+    dyn DynHash + Send,
+    dyn DynHash + Sync,
+    dyn DynHash + Send + Sync
+);
+impl_hash!(
+    dyn DynHash,
+    dyn DynHash + Send,
+    dyn DynHash + Sync,
+    dyn DynHash + Send + Sync
+);
+
+/// An [`AnyDebug`] value which can be ordered relative to other dynamically typed values.
+///
+/// This trait is implemented for all types which implement [`AnyDebug`] and [`Ord`].
+/// Values of the same type are ordered using that type's `Ord` implementation.
+/// Values of different types are ordered using [`cmp_by_type`], so that all values of one type
+/// sort together.
+/// See its documentation for when the order between types is reproducible.
+///
+/// `dyn DynOrd` (and its combinations with `Send` and `Sync`) implement `Ord`, so collections
+/// of boxed values can be sorted.
+/// They also have the same downcasting methods as `dyn AnyDebug`.
+///
+/// To sort values which don't all implement `Ord`, such as a `Vec<Box<dyn AnyDebug>>`, use
+/// [`cmp_by_type`] directly.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::DynOrd;
+///
+/// #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// struct Priority(u8);
+///
+/// let mut pending: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(Priority(2)),
+///     Box::new("redraw"),
+///     Box::new(Priority(1)),
+/// ];
+/// pending.sort();
+/// let priorities: Vec<u8> = pending
+///     .iter()
+///     .filter_map(|message| message.downcast_ref::<Priority>())
+///     .map(|priority| priority.0)
+///     .collect();
+/// assert_eq!(priorities, [1, 2]);
+/// ```
+pub trait DynOrd: DynPartialEq {
+    /// Returns the ordering between this value and `other`.
+    ///
+    /// See the [trait level documentation](DynOrd) for details of how values of different
+    /// types are ordered.
+    fn dyn_cmp(&self, other: &dyn DynOrd) -> Ordering;
+}
+
+impl<T: AnyDebug + Ord> DynOrd for T {
+    fn dyn_cmp(&self, other: &dyn DynOrd) -> Ordering {
+        match other.downcast_ref::<T>() {
+            Some(other) => self.cmp(other),
+            None => cmp_by_type(self, other),
+        }
+    }
+}
+
+/// Orders dynamically typed values by their types.
+///
+/// If the `type_name` feature is enabled, types are ordered by their names, so the order is
+/// reproducible between builds of a program (as long as the type names don't change).
+/// Types with the same name, and all types if the `type_name` feature is disabled, are then
+/// ordered by their [`TypeId`]s, which is only consistent within a single build.
+///
+/// Values of the same type are equal according to this function, so a stable sort keeps
+/// them in their original order.
+/// To also order values of the same type, use [`DynOrd`].
+///
+/// The same [smart pointer caveat](crate#smart-pointers-and-dyn-anydebug) applies as for
+/// [`AnyDebug::type_name`].
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{cmp_by_type, AnyDebug};
+///
+/// #[derive(Debug)]
+/// struct Redraw;
+///
+/// let mut pending: Vec<Box<dyn AnyDebug>> = vec![Box::new(2_u8), Box::new(Redraw), Box::new(1_u8)];
+/// pending.sort_by(|a, b| cmp_by_type(&**a, &**b));
+/// # #[cfg(feature = "type_name")]
+/// assert_eq!(format!("{pending:?}"), "[Redraw, 2, 1]");
+/// ```
+pub fn cmp_by_type(a: &dyn AnyDebug, b: &dyn AnyDebug) -> Ordering {
+    #[cfg(feature = "type_name")]
+    let by_name = a.type_name().cmp(b.type_name());
+    #[cfg(not(feature = "type_name"))]
+    let by_name = Ordering::Equal;
+    by_name.then_with(|| a.type_id().cmp(&b.type_id()))
+}
+
+macro_rules! impl_ord {
+    ($($ty:ty),+) => {
+        $(
+            impl PartialOrd for $ty {
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl Ord for $ty {
+                fn cmp(&self, other: &Self) -> Ordering {
+                    self.dyn_cmp(other)
+                }
+            }
+
+            impl Eq for $ty {}
+        )+
+    };
+}
+
+impl_partial_eq!(
+    dyn DynOrd,
+    dyn DynOrd + Send,
+    dyn DynOrd + Sync,
+    dyn DynOrd + Send + Sync
+);
+impl_ord!(
+    dyn DynOrd,
+    dyn DynOrd + Send,
+    dyn DynOrd + Sync,
+    dyn DynOrd + Send + Sync
+);
+
+crate::impl_downcast!(dyn DynPartialEq);
+crate::impl_downcast!(dyn DynPartialEq + Send);
+crate::impl_downcast!(dyn DynPartialEq + Sync);
+crate::impl_downcast!(dyn DynPartialEq + Send + Sync);
+crate::impl_downcast!(dyn DynHash);
+crate::impl_downcast!(dyn DynHash + Send);
+crate::impl_downcast!(dyn DynHash + Sync);
+crate::impl_downcast!(dyn DynHash + Send + Sync);
+crate::impl_downcast!(dyn DynOrd);
+crate::impl_downcast!(dyn DynOrd + Send);
+crate::impl_downcast!(dyn DynOrd + Sync);
+crate::impl_downcast!(dyn DynOrd + Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    use super::{DynHash, DynOrd, DynPartialEq};
+    use crate::AnyDebug;
+    use core::cmp::Ordering;
+
+    #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn dyn_partial_eq_same_type() {
+        let a: &dyn DynPartialEq = &SomeMessage(80);
+        let b: &dyn DynPartialEq = &SomeMessage(80);
+        let c: &dyn DynPartialEq = &SomeMessage(81);
+        assert!(a == b);
+        assert!(a != c);
+    }
+    #[test]
+    fn dyn_partial_eq_different_type() {
+        let a: &(dyn DynPartialEq + Send) = &SomeMessage(82);
+        let b: &(dyn DynPartialEq + Send) = &82_u32;
+        assert!(a != b);
+        assert!(!a.dyn_eq(&82_u32 as &dyn AnyDebug));
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn dyn_partial_eq_assert_boxed() {
+        extern crate alloc;
+        use alloc::boxed::Box;
+
+        let a: Box<dyn DynPartialEq> = Box::new(SomeMessage(84));
+        let b: Box<dyn DynPartialEq> = Box::new(SomeMessage(84));
+        assert_eq!(*a, *b);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn dyn_partial_eq_boxed() {
+        extern crate alloc;
+        use alloc::{boxed::Box, vec, vec::Vec};
+
+        let mut queue: Vec<Box<dyn DynPartialEq + Send + Sync>> = vec![
+            Box::new(SomeMessage(83)),
+            Box::new(SomeMessage(83)),
+            Box::new(83_u32),
+        ];
+        queue.dedup();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(
+            queue[0].downcast_ref::<SomeMessage>(),
+            Some(&SomeMessage(83))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn dyn_partial_eq_is_nested_box() {
+        extern crate alloc;
+        use alloc::boxed::Box;
+
+        let val: Box<dyn DynPartialEq> = Box::new(SomeMessage(85));
+        assert!(!val.is_nested_box());
+        let val: Box<dyn DynPartialEq> = Box::new(val);
+        assert!(val.is_nested_box());
+        let val: Box<dyn AnyDebug> =
+            Box::new(Box::new(SomeMessage(86)) as Box<dyn DynPartialEq + Send>);
+        assert!(val.is_nested_box());
+    }
+
+    #[test]
+    fn dyn_hash_includes_type() {
+        extern crate std;
+        use core::hash::BuildHasher;
+        use std::hash::RandomState;
+
+        let state = RandomState::new();
+        let a: &dyn DynHash = &SomeMessage(90);
+        let b: &dyn DynHash = &SomeMessage(90);
+        let c: &dyn DynHash = &90_u32;
+        assert_eq!(state.hash_one(a), state.hash_one(b));
+        assert_ne!(state.hash_one(a), state.hash_one(c));
+    }
+    #[test]
+    fn dyn_hash_map_key() {
+        extern crate std;
+        use std::boxed::Box;
+        use std::collections::HashMap;
+
+        let mut map: HashMap<Box<dyn DynHash + Send + Sync>, u32> = HashMap::new();
+        map.insert(Box::new(SomeMessage(91)), 1);
+        map.insert(Box::new(91_u32), 2);
+        map.insert(Box::new(SomeMessage(91)), 3);
+        assert_eq!(map.len(), 2);
+        let key: Box<dyn DynHash + Send + Sync> = Box::new(SomeMessage(91));
+        assert_eq!(map[&key], 3);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn dyn_hash_is_nested_box() {
+        extern crate std;
+        use std::boxed::Box;
+
+        let val: Box<dyn DynHash + Send + Sync> = Box::new(SomeMessage(92));
+        assert!(!val.is_nested_box());
+        // A nested key never matches the value inside it.
+        let val: Box<dyn DynHash + Send + Sync> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+
+    #[test]
+    fn dyn_ord_same_type() {
+        let a: &dyn DynOrd = &SomeMessage(100);
+        let b: &dyn DynOrd = &SomeMessage(101);
+        assert_eq!(a.cmp(b), Ordering::Less);
+        assert!(a < b);
+        assert_eq!(a.cmp(a), Ordering::Equal);
+    }
+    #[test]
+    fn dyn_ord_different_types() {
+        let a: &(dyn DynOrd + Send + Sync) = &SomeMessage(102);
+        let b: &(dyn DynOrd + Send + Sync) = &102_u32;
+        assert_ne!(a.cmp(b), Ordering::Equal);
+        assert_eq!(a.cmp(b), b.cmp(a).reverse());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn dyn_ord_sort_groups_types() {
+        extern crate alloc;
+        use alloc::{boxed::Box, vec, vec::Vec};
+
+        let mut values: Vec<Box<dyn DynOrd>> = vec![
+            Box::new(SomeMessage(104)),
+            Box::new(1_u32),
+            Box::new(SomeMessage(103)),
+            Box::new(0_u32),
+        ];
+        values.sort();
+        let first_is_message = values[0].is::<SomeMessage>();
+        assert_eq!(values[1].is::<SomeMessage>(), first_is_message);
+        assert_eq!(values[2].is::<SomeMessage>(), !first_is_message);
+        let messages: Vec<u32> = values
+            .iter()
+            .filter_map(|value| value.downcast_ref::<SomeMessage>())
+            .map(|message| message.0)
+            .collect();
+        assert_eq!(messages, [103, 104]);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn dyn_ord_is_nested_box() {
+        extern crate alloc;
+        use alloc::boxed::Box;
+
+        let val: Box<dyn DynOrd + Send + Sync> = Box::new(SomeMessage(107));
+        assert!(!val.is_nested_box());
+        let val: Box<dyn DynOrd + Send + Sync> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "type_name")]
+    fn cmp_by_type_uses_type_name() {
+        use super::cmp_by_type;
+
+        let a: &dyn AnyDebug = &SomeMessage(105);
+        let b: &dyn AnyDebug = &105_u32;
+        // `anymore::cmp::tests::SomeMessage` sorts before `u32`.
+        assert_eq!(cmp_by_type(a, b), Ordering::Less);
+        assert_eq!(cmp_by_type(a, &SomeMessage(106)), Ordering::Equal);
+        let a: &dyn DynOrd = &SomeMessage(105);
+        assert_eq!(a.cmp(&105_u32), Ordering::Less);
+    }
+}
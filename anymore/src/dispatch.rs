@@ -0,0 +1,474 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Calling a handler for each type of dynamically typed message.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::{self, Vec};
+use core::any::TypeId;
+use core::fmt::{self, Debug, Display};
+
+use crate::map::TypeLabel;
+use crate::AnyDebug;
+
+/// A handler registered for a single type, which is given the message as a
+/// `Box<dyn AnyDebug>` of that type.
+type BoxedHandler<C> = Box<dyn FnMut(Box<dyn AnyDebug>, &mut C)>;
+
+/// A set of handlers for messages of different types.
+///
+/// A handler is registered for each type of message using [`register`](Self::register),
+/// and [`dispatch`](Self::dispatch) calls the handler for the type of a message.
+/// This replaces chains of `downcast_ref` calls, one for each type of message.
+/// Messages for which there is no handler are returned in an [`Unhandled`] error, which
+/// describes them in its `Display` output.
+///
+/// Each handler is also given a context of type `C`, such as the state of the application.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyDebug;
+/// use anymore::dispatch::HandlerRegistry;
+///
+/// #[derive(Debug)]
+/// struct Click { x: f64 }
+/// #[derive(Debug)]
+/// struct Scroll { delta: f64 }
+///
+/// let mut handlers = HandlerRegistry::<Vec<String>>::new();
+/// handlers.register(|click: Click, log: &mut Vec<String>| log.push(format!("click at {}", click.x)));
+/// handlers.register(|scroll: Scroll, log: &mut Vec<String>| log.push(format!("scroll by {}", scroll.delta)));
+///
+/// let mut log = Vec::new();
+/// handlers.dispatch(Box::new(Click { x: 1.5 }), &mut log).unwrap();
+/// let error = handlers.dispatch(Box::new(7_u8), &mut log).unwrap_err();
+/// assert_eq!(log, ["click at 1.5"]);
+/// assert_eq!(error.message().downcast_ref::<u8>(), Some(&7));
+/// # #[cfg(feature = "type_name")]
+/// assert_eq!(error.to_string(), "no handler for message of type u8: 7");
+/// ```
+pub struct HandlerRegistry<C = ()> {
+    handlers: BTreeMap<TypeId, Handler<C>>,
+}
+
+/// The handler for a single type.
+struct Handler<C> {
+    #[cfg(feature = "type_name")]
+    type_name: &'static str,
+    call: BoxedHandler<C>,
+}
+
+impl<C> HandlerRegistry<C> {
+    /// Creates a registry with no handlers.
+    pub fn new() -> Self {
+        Self {
+            handlers: BTreeMap::new(),
+        }
+    }
+
+    /// Registers `handler` to be called with messages of type `T`.
+    ///
+    /// This replaces the handler previously registered for `T`, if there is one.
+    pub fn register<T: AnyDebug>(&mut self, mut handler: impl FnMut(T, &mut C) + 'static) {
+        let handler = Handler {
+            #[cfg(feature = "type_name")]
+            type_name: core::any::type_name::<T>(),
+            call: Box::new(move |message: Box<dyn AnyDebug>, context: &mut C| {
+                handler(*message.downcast::<T>().unwrap(), context);
+            }),
+        };
+        self.handlers.insert(TypeId::of::<T>(), handler);
+    }
+
+    /// Returns `true` if a handler has been registered for `T`.
+    pub fn is_registered<T: AnyDebug>(&self) -> bool {
+        self.handlers.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Calls the handler registered for the type of `message`, with `message` and `context`.
+    ///
+    /// The same [smart pointer caveat](crate#smart-pointers-and-dyn-anydebug) applies as for
+    /// [`AnyDebug::type_name`], so `message` should not be a box containing another box.
+    ///
+    /// ## Errors
+    ///
+    /// If no handler has been registered for the type of `message`, returns `message` in
+    /// an [`Unhandled`] error.
+    pub fn dispatch(
+        &mut self,
+        message: Box<dyn AnyDebug>,
+        context: &mut C,
+    ) -> Result<(), Unhandled> {
+        match self.handlers.get_mut(&(*message).type_id()) {
+            Some(handler) => {
+                (handler.call)(message, context);
+                Ok(())
+            }
+            None => Err(Unhandled { message }),
+        }
+    }
+
+    /// Returns the number of types for which a handler has been registered.
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Returns `true` if no handlers have been registered.
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+impl<C> Default for HandlerRegistry<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Debug for HandlerRegistry<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut types = f.debug_set();
+        #[cfg(feature = "type_name")]
+        for handler in self.handlers.values() {
+            types.entry(&format_args!("{}", handler.type_name));
+        }
+        #[cfg(not(feature = "type_name"))]
+        for id in self.handlers.keys() {
+            types.entry(&crate::ShortTypeId::from(*id));
+        }
+        types.finish()
+    }
+}
+
+/// The error returned by [`HandlerRegistry::dispatch`] when there is no handler for a message.
+///
+/// This contains the message, so that it can be handled in another way.
+/// Its `Display` output includes the type name (if the `type_name` feature is enabled) and
+/// the `Debug` output of the message.
+pub struct Unhandled {
+    message: Box<dyn AnyDebug>,
+}
+
+impl Unhandled {
+    /// The message which wasn't handled.
+    pub fn message(&self) -> &dyn AnyDebug {
+        &*self.message
+    }
+
+    /// Returns the message which wasn't handled.
+    pub fn into_message(self) -> Box<dyn AnyDebug> {
+        self.message
+    }
+}
+
+impl Debug for Unhandled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Unhandled").field(&self.message).finish()
+    }
+}
+
+impl Display for Unhandled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no handler for message of type {:?}: {:?}",
+            TypeLabel(&*self.message),
+            self.message
+        )
+    }
+}
+
+impl core::error::Error for Unhandled {}
+
+/// A collection of messages which couldn't be delivered, for later inspection.
+///
+/// Rather than dropping messages for which there was no handler, or which were not of the
+/// expected type, they can be collected here as [`DeadLetter`]s.
+/// This allows tests and debugging tools to show which messages went missing, and why.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::dispatch::{DeadLetterReason, DeadLetters, HandlerRegistry};
+///
+/// #[derive(Debug)]
+/// struct Click { x: f64 }
+///
+/// let mut handlers = HandlerRegistry::new();
+/// handlers.register(|_: Click, _: &mut ()| {});
+/// let mut dead_letters = DeadLetters::new();
+/// if let Err(error) = handlers.dispatch(Box::new(7_u8), &mut ()) {
+///     dead_letters.push(error.into());
+/// }
+///
+/// let letters: Vec<_> = dead_letters.drain().collect();
+/// assert_eq!(letters[0].debug(), "7");
+/// assert_eq!(letters[0].reason(), &DeadLetterReason::Unhandled);
+/// # #[cfg(feature = "type_name")]
+/// assert_eq!(letters[0].to_string(), "u8: 7 (no handler was registered)");
+/// assert!(dead_letters.is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct DeadLetters {
+    letters: Vec<DeadLetter>,
+}
+
+impl DeadLetters {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `letter` to the collection.
+    pub fn push(&mut self, letter: DeadLetter) {
+        self.letters.push(letter);
+    }
+
+    /// Returns an iterator over the collected messages, from the oldest to the newest.
+    pub fn iter(&self) -> core::slice::Iter<'_, DeadLetter> {
+        self.letters.iter()
+    }
+
+    /// Removes all of the collected messages, returning them in an iterator from the oldest
+    /// to the newest.
+    pub fn drain(&mut self) -> vec::Drain<'_, DeadLetter> {
+        self.letters.drain(..)
+    }
+
+    /// Returns the number of collected messages.
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    /// Returns `true` if no messages have been collected.
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a DeadLetters {
+    type Item = &'a DeadLetter;
+    type IntoIter = core::slice::Iter<'a, DeadLetter>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A message which couldn't be delivered, stored in [`DeadLetters`].
+///
+/// This records the `Debug` output of the message at the time it couldn't be delivered,
+/// and the reason why.
+/// Its `Display` output includes the type name (if the `type_name` feature is enabled),
+/// the `Debug` output and the reason.
+pub struct DeadLetter {
+    message: Box<dyn AnyDebug>,
+    debug: String,
+    reason: DeadLetterReason,
+}
+
+impl DeadLetter {
+    /// Records that `message` couldn't be delivered because of `reason`.
+    ///
+    /// This captures the `Debug` output of `message`.
+    pub fn new(message: Box<dyn AnyDebug>, reason: DeadLetterReason) -> Self {
+        Self {
+            debug: alloc::format!("{message:?}"),
+            message,
+            reason,
+        }
+    }
+
+    /// The message which couldn't be delivered.
+    pub fn message(&self) -> &dyn AnyDebug {
+        &*self.message
+    }
+
+    /// Returns the message which couldn't be delivered.
+    pub fn into_message(self) -> Box<dyn AnyDebug> {
+        self.message
+    }
+
+    /// The name of the type of the message.
+    ///
+    /// This method is only available if the `type_name` feature is enabled.
+    #[cfg(feature = "type_name")]
+    pub fn type_name(&self) -> &'static str {
+        (*self.message).type_name()
+    }
+
+    /// A short identifier for the type of the message.
+    pub fn short_type_id(&self) -> crate::ShortTypeId {
+        crate::ShortTypeId::of_val(&*self.message)
+    }
+
+    /// The `Debug` output of the message, captured when it couldn't be delivered.
+    pub fn debug(&self) -> &str {
+        &self.debug
+    }
+
+    /// The reason why the message couldn't be delivered.
+    pub fn reason(&self) -> &DeadLetterReason {
+        &self.reason
+    }
+}
+
+impl From<Unhandled> for DeadLetter {
+    fn from(error: Unhandled) -> Self {
+        Self::new(error.message, DeadLetterReason::Unhandled)
+    }
+}
+
+impl Debug for DeadLetter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeadLetter")
+            .field("type", &TypeLabel(&*self.message))
+            .field("debug", &self.debug)
+            .field("reason", &self.reason)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Display for DeadLetter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}: {} ({})",
+            TypeLabel(&*self.message),
+            self.debug,
+            self.reason
+        )
+    }
+}
+
+/// The reason why a [`DeadLetter`] couldn't be delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// No handler was registered for the type of the message.
+    Unhandled,
+    /// The message was not of the type which its receiver expected.
+    WrongType {
+        /// The name of the type which was expected.
+        expected: &'static str,
+    },
+    /// Another reason, described by the application.
+    Other(Cow<'static, str>),
+}
+
+impl DeadLetterReason {
+    /// The reason for a message which was not of type `T`, when a `T` was expected.
+    pub fn wrong_type<T: ?Sized + 'static>() -> Self {
+        Self::WrongType {
+            expected: core::any::type_name::<T>(),
+        }
+    }
+}
+
+impl Display for DeadLetterReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unhandled => f.write_str("no handler was registered"),
+            Self::WrongType { expected } => write!(f, "expected a message of type {expected}"),
+            Self::Other(reason) => f.write_str(reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeadLetter, DeadLetterReason, DeadLetters, HandlerRegistry};
+    use alloc::boxed::Box;
+    use alloc::format;
+    use alloc::vec::Vec;
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn handler_registry_dispatch() {
+        let mut handlers = HandlerRegistry::<Vec<u32>>::new();
+        handlers.register(|message: SomeMessage, seen: &mut Vec<u32>| seen.push(message.0));
+        handlers.register(|message: u32, seen: &mut Vec<u32>| seen.push(message + 1));
+        assert!(handlers.is_registered::<SomeMessage>());
+        assert_eq!(handlers.len(), 2);
+
+        let mut seen = Vec::new();
+        handlers
+            .dispatch(Box::new(SomeMessage(290)), &mut seen)
+            .unwrap();
+        handlers.dispatch(Box::new(290_u32), &mut seen).unwrap();
+        assert_eq!(seen, [290, 291]);
+    }
+    #[test]
+    fn handler_registry_unhandled() {
+        let mut handlers = HandlerRegistry::new();
+        handlers.register(|_: u32, _: &mut ()| {});
+        let error = handlers
+            .dispatch(Box::new(SomeMessage(292)), &mut ())
+            .unwrap_err();
+        assert_eq!(format!("{error:?}"), "Unhandled(SomeMessage(292))");
+        #[cfg(feature = "type_name")]
+        assert!(format!("{error}").ends_with("::SomeMessage: SomeMessage(292)"));
+        let message = error.into_message();
+        assert_eq!(message.downcast_ref::<SomeMessage>().unwrap().0, 292);
+    }
+    #[test]
+    fn handler_registry_replace() {
+        let mut handlers = HandlerRegistry::<u32>::new();
+        handlers.register(|message: u32, total: &mut u32| *total += message);
+        handlers.register(|message: u32, total: &mut u32| *total += message * 2);
+        let mut total = 0;
+        handlers.dispatch(Box::new(293_u32), &mut total).unwrap();
+        assert_eq!(total, 586);
+        #[cfg(feature = "type_name")]
+        assert_eq!(format!("{handlers:?}"), "{u32}");
+    }
+    #[test]
+    fn dead_letters_unhandled() {
+        let mut handlers = HandlerRegistry::new();
+        let mut dead_letters = DeadLetters::new();
+        if let Err(error) = handlers.dispatch(Box::new(SomeMessage(310)), &mut ()) {
+            dead_letters.push(error.into());
+        }
+        let letter = dead_letters.iter().next().unwrap();
+        assert_eq!(letter.debug(), "SomeMessage(310)");
+        assert_eq!(letter.reason(), &DeadLetterReason::Unhandled);
+        assert_eq!(
+            letter.message().downcast_ref::<SomeMessage>().unwrap().0,
+            310
+        );
+        #[cfg(feature = "type_name")]
+        assert!(letter.type_name().ends_with("::SomeMessage"));
+        assert_eq!(dead_letters.len(), 1);
+    }
+    #[test]
+    fn dead_letters_drain() {
+        let mut dead_letters = DeadLetters::new();
+        dead_letters.push(DeadLetter::new(
+            Box::new(311_u32),
+            DeadLetterReason::wrong_type::<SomeMessage>(),
+        ));
+        dead_letters.push(DeadLetter::new(
+            Box::new(312_u32),
+            DeadLetterReason::Other("queue full".into()),
+        ));
+        let letters: Vec<_> = dead_letters.drain().collect();
+        assert!(dead_letters.is_empty());
+        assert_eq!(letters.len(), 2);
+        #[cfg(feature = "type_name")]
+        {
+            assert!(format!("{}", letters[0]).starts_with(
+                "u32: 311 (expected a message of type anymore::dispatch::tests::SomeMessage"
+            ));
+            assert_eq!(
+                format!("{:?}", letters[1]),
+                r#"DeadLetter { type: u32, debug: "312", reason: Other("queue full"), .. }"#
+            );
+        }
+        assert_eq!(*letters[1].message().downcast_ref::<u32>().unwrap(), 312);
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Dynamically typed values which are rendered using [`Display`].
+
+use core::any::Any;
+use core::fmt::Display;
+
+use crate::AnyDebug;
+
+/// A trait to implement dynamic typing for values which are shown to users.
+///
+/// This trait is the same as [`AnyDebug`], except that it requires [`Display`] rather
+/// than [`Debug`](core::fmt::Debug).
+/// This is useful for values such as user-facing strings and errors, for which the `Display`
+/// output is the meaningful rendering.
+/// For values which implement both, see [`AnyDebugDisplay`].
+///
+/// `dyn AnyDisplay` (and its combinations with `Send` and `Sync`) have the same downcasting
+/// methods as `dyn AnyDebug`.
+/// Errors and panic messages from these methods include the `Display` output of the value,
+/// rather than its `Debug` output.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyDisplay;
+///
+/// let status: &dyn AnyDisplay = &"Saved";
+/// assert_eq!(status.to_string(), "Saved");
+/// assert!(status.is::<&str>());
+/// ```
+pub trait AnyDisplay: Any + Display {
+    /// Returns the [`type_name`](core::any::type_name) of this value's concrete type.
+    ///
+    /// See [`AnyDebug::type_name`] for details.
+    #[cfg(feature = "type_name")]
+    fn type_name(&self) -> &'static str;
+}
+
+impl<T: Any + Display> AnyDisplay for T {
+    #[cfg(feature = "type_name")]
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<Self>()
+    }
+}
+
+crate::impl_downcast!(@bound AnyDisplay, "`Display` output"; dyn AnyDisplay);
+crate::impl_downcast!(@bound AnyDisplay, "`Display` output"; dyn AnyDisplay + Send);
+crate::impl_downcast!(@bound AnyDisplay, "`Display` output"; dyn AnyDisplay + Sync);
+crate::impl_downcast!(@bound AnyDisplay, "`Display` output"; dyn AnyDisplay + Send + Sync);
+
+/// A dynamically typed value which implements both [`Debug`](core::fmt::Debug) and [`Display`].
+///
+/// This trait is implemented for all types which implement [`AnyDebug`] and [`Display`].
+/// `dyn AnyDebugDisplay` (and its combinations with `Send` and `Sync`) can be formatted using
+/// either, and have the same downcasting methods as `dyn AnyDebug`.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyDebugDisplay;
+///
+/// let count: &dyn AnyDebugDisplay = &"three";
+/// assert_eq!(format!("{count}"), "three");
+/// assert_eq!(format!("{count:?}"), "\"three\"");
+/// assert_eq!(*count.expect_downcast_ref::<&str>(), "three");
+/// ```
+pub trait AnyDebugDisplay: AnyDebug + Display {}
+
+impl<T: AnyDebug + Display> AnyDebugDisplay for T {}
+
+crate::impl_downcast!(dyn AnyDebugDisplay);
+crate::impl_downcast!(dyn AnyDebugDisplay + Send);
+crate::impl_downcast!(dyn AnyDebugDisplay + Sync);
+crate::impl_downcast!(dyn AnyDebugDisplay + Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::{AnyDebugDisplay, AnyDisplay};
+    #[cfg(feature = "alloc")]
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use core::fmt;
+
+    struct SomeStatus(u32);
+
+    impl fmt::Display for SomeStatus {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "status {}", self.0)
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "type_name")]
+    fn any_display_correct_typename() {
+        let val: &dyn AnyDisplay = &SomeStatus(120);
+        assert!(val.type_name().contains("SomeStatus"));
+    }
+    #[test]
+    fn any_display_correct_display() {
+        let val: &(dyn AnyDisplay + Send) = &SomeStatus(121);
+        assert_eq!(val.to_string(), "status 121");
+    }
+    #[test]
+    fn any_display_downcast_ref() {
+        let val: &(dyn AnyDisplay + Sync) = &SomeStatus(122);
+        assert!(!val.is::<u32>());
+        assert_eq!(val.downcast_ref::<SomeStatus>().unwrap().0, 122);
+    }
+    #[test]
+    fn any_display_downcast_mut() {
+        let mut val = SomeStatus(123);
+        let val_mut: &mut (dyn AnyDisplay + Send + Sync) = &mut val;
+        val_mut.downcast_mut::<SomeStatus>().unwrap().0 = 124;
+        assert!(val_mut.downcast_mut::<u32>().is_none());
+        assert_eq!(val.0, 124);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_display_downcast() {
+        let val: Box<dyn AnyDisplay> = Box::new(SomeStatus(125));
+        let val = val.downcast::<u32>().err().unwrap();
+        assert_eq!(val.downcast::<SomeStatus>().ok().unwrap().0, 125);
+    }
+    #[test]
+    #[cfg(feature = "type_name")]
+    fn any_display_try_downcast_ref() {
+        let val: &dyn AnyDisplay = &SomeStatus(127);
+        let err = val.try_downcast_ref::<u32>().unwrap_err();
+        assert_eq!(err.expected(), "u32");
+        assert!(err.actual().ends_with("SomeStatus"));
+    }
+    #[test]
+    #[should_panic(expected = "status 128")]
+    fn any_display_expect_downcast_ref_panics() {
+        let val: &(dyn AnyDisplay + Send) = &SomeStatus(128);
+        val.expect_downcast_ref::<u32>();
+    }
+    #[test]
+    #[cfg(all(feature = "alloc", feature = "type_name"))]
+    fn any_display_downcast_debug() {
+        let val: Box<dyn AnyDisplay + Send + Sync> = Box::new(SomeStatus(129));
+        let err = val.downcast_debug::<u32>().unwrap_err();
+        assert_eq!(err.debug(), "status 129");
+        let nested: Box<dyn AnyDisplay> =
+            Box::new(Box::new(SomeStatus(129)) as Box<dyn AnyDisplay>);
+        assert!(nested.is_nested_box());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_display_is_nested_box() {
+        let val: Box<dyn AnyDebugDisplay> = Box::new(130_u32);
+        assert!(!val.is_nested_box());
+        let val: Box<dyn AnyDebugDisplay + Send + Sync> =
+            Box::new(Box::new(131_u32) as Box<dyn AnyDebugDisplay + Send + Sync>);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    fn any_debug_display_formatting() {
+        let val: &(dyn AnyDebugDisplay + Send + Sync) = &126_u32;
+        assert_eq!(val.to_string(), "126");
+        assert_eq!(alloc::format!("{val:?}"), "126");
+        assert_eq!(*val.expect_downcast_ref::<u32>(), 126);
+    }
+}
@@ -0,0 +1,412 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Errors describing failed downcasts.
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String};
+use core::fmt::{self, Display};
+#[cfg(feature = "alloc")]
+use core::hash::Hasher;
+#[cfg(feature = "alloc")]
+use core::panic::Location;
+#[cfg(feature = "std")]
+use {alloc::sync::Arc, std::backtrace::Backtrace};
+
+use crate::AnyDebug;
+
+/// The error returned when a `dyn AnyDebug` value is not of the expected type.
+///
+/// This records the name of the type which was expected, and the name of the type
+/// which was actually found.
+/// It is returned by the `try_downcast_ref` and `try_downcast_mut` methods of `dyn AnyDebug`.
+///
+/// To also capture the [`Debug`](core::fmt::Debug) output of the value, see [`DowncastError`].
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyDebug;
+///
+/// #[derive(Debug)]
+/// struct Click;
+///
+/// let message: &dyn AnyDebug = &Click;
+/// let error = message.try_downcast_ref::<u32>().unwrap_err();
+/// assert_eq!(error.expected(), "u32");
+/// assert!(error.actual().ends_with("Click"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TypeMismatch {
+    expected: &'static str,
+    actual: &'static str,
+}
+
+impl TypeMismatch {
+    /// Creates the error for `value` not being of the type `T`.
+    pub fn new<T: ?Sized>(value: &dyn AnyDebug) -> Self {
+        Self::from_names(core::any::type_name::<T>(), value.type_name())
+    }
+
+    /// Creates the error for a value of the type named `actual` not being of the type named
+    /// `expected`.
+    pub(crate) fn from_names(expected: &'static str, actual: &'static str) -> Self {
+        Self { expected, actual }
+    }
+
+    /// The name of the type which the value was expected to be.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// The name of the type which the value actually was.
+    pub fn actual(&self) -> &'static str {
+        self.actual
+    }
+}
+
+impl Display for TypeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected a value of type `{}`, but got a value of type `{}`",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl core::error::Error for TypeMismatch {}
+
+/// The error returned when a list of `dyn AnyDebug` values doesn't match the expected types.
+///
+/// This is returned by [`downcast_tuple`](crate::downcast_tuple).
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{AnyDebug, ArgumentError};
+///
+/// let args: [&dyn AnyDebug; 2] = [&"open", &"file.txt"];
+/// let error = anymore::downcast_tuple::<(&str, u32)>(&args).unwrap_err();
+/// let ArgumentError::Type { position, mismatch } = error else {
+///     panic!("Expected a type mismatch, got {error:?}");
+/// };
+/// assert_eq!(position, 1);
+/// assert_eq!(mismatch.expected(), "u32");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgumentError {
+    /// There were a different number of values than expected.
+    Count {
+        /// The number of values which were expected.
+        expected: usize,
+        /// The number of values which were provided.
+        actual: usize,
+    },
+    /// The value at `position` was not of the expected type.
+    Type {
+        /// The index of the value which was of the wrong type.
+        position: usize,
+        /// The expected and actual types of the value.
+        mismatch: TypeMismatch,
+    },
+}
+
+impl Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count { expected, actual } => {
+                write!(f, "expected {expected} arguments, but got {actual}")
+            }
+            Self::Type { position, mismatch } => write!(f, "argument {position}: {mismatch}"),
+        }
+    }
+}
+
+impl core::error::Error for ArgumentError {}
+
+/// The error returned when downcasting a `dyn AnyDebug` value to a type which it isn't.
+///
+/// This records the name of the type which was expected, the name of the type
+/// which was actually found, and the [`Debug`](core::fmt::Debug) output of the value,
+/// so that the unexpected value can be inspected after the fact.
+///
+/// This is returned by the `downcast_debug` method of `dyn AnyDebug`.
+/// It implements [`Error`](core::error::Error), so can be propagated using `?`.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyDebug;
+///
+/// #[derive(Debug)]
+/// struct Click { x: f64 }
+///
+/// let message: Box<dyn AnyDebug> = Box::new(Click { x: 1.5 });
+/// let error = message.downcast_debug::<u32>().unwrap_err();
+/// assert_eq!(error.expected(), "u32");
+/// assert!(error.actual().ends_with("Click"));
+/// assert_eq!(error.debug(), "Click { x: 1.5 }");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct DowncastError {
+    mismatch: TypeMismatch,
+    debug: String,
+    location: &'static Location<'static>,
+    #[cfg(feature = "std")]
+    backtrace: Arc<Backtrace>,
+}
+
+#[cfg(feature = "alloc")]
+impl DowncastError {
+    /// Creates the error for a failed downcast of `value` to the type `T`.
+    ///
+    /// If the `std` feature is enabled, this also captures a `std::backtrace::Backtrace`, if
+    /// backtraces are enabled by the environment variables described in its documentation.
+    #[track_caller]
+    pub fn new<T: ?Sized>(value: &dyn AnyDebug) -> Self {
+        Self::from_parts(TypeMismatch::new::<T>(value), format!("{value:?}"))
+    }
+
+    /// Creates the error for the failed downcast described by `mismatch`, of a value which is
+    /// formatted as `debug`.
+    #[track_caller]
+    pub(crate) fn from_parts(mismatch: TypeMismatch, debug: String) -> Self {
+        Self {
+            mismatch,
+            debug,
+            location: Location::caller(),
+            #[cfg(feature = "std")]
+            backtrace: Arc::new(Backtrace::capture()),
+        }
+    }
+
+    /// The name of the type which the value was expected to be.
+    pub fn expected(&self) -> &'static str {
+        self.mismatch.expected
+    }
+
+    /// The name of the type which the value actually was.
+    pub fn actual(&self) -> &'static str {
+        self.mismatch.actual
+    }
+
+    /// The [`Debug`](core::fmt::Debug) output of the value.
+    ///
+    /// For a value downcast from a `dyn AnyDisplay`, this is its `Display` output instead.
+    pub fn debug(&self) -> &str {
+        &self.debug
+    }
+
+    /// The location in the source code where the downcast failed.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Returns the facts about this error in a structured form, for crash reporters and logs.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use anymore::AnyDebug;
+    ///
+    /// let message: Box<dyn AnyDebug> = Box::new("x".repeat(2000));
+    /// let report = message.downcast_debug::<u32>().unwrap_err().to_report();
+    /// assert_eq!(report.expected(), "u32");
+    /// assert_eq!(report.location().file(), file!());
+    /// assert!(report.is_debug_truncated());
+    /// assert_eq!(report.debug().len(), anymore::ErrorReport::MAX_DEBUG_LEN);
+    /// ```
+    pub fn to_report(&self) -> ErrorReport {
+        let mut debug_len = self.debug.len().min(ErrorReport::MAX_DEBUG_LEN);
+        while !self.debug.is_char_boundary(debug_len) {
+            debug_len -= 1;
+        }
+        ErrorReport {
+            mismatch: self.mismatch,
+            debug: self.debug[..debug_len].into(),
+            debug_truncated: debug_len < self.debug.len(),
+            location: self.location,
+            #[cfg(feature = "std")]
+            backtrace: self.backtrace.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for DowncastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.mismatch, self.debug)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::error::Error for DowncastError {}
+
+/// The facts about a [`DowncastError`], in a form which can be recorded by crash reporters.
+///
+/// This is returned by [`DowncastError::to_report`].
+/// Unlike the `Display` output of the error, each fact is available separately, so
+/// doesn't need to be parsed back out of a message.
+/// The `Debug` output of the value is truncated to [`MAX_DEBUG_LEN`](Self::MAX_DEBUG_LEN) bytes,
+/// so that very large values don't produce very large reports.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct ErrorReport {
+    mismatch: TypeMismatch,
+    debug: String,
+    debug_truncated: bool,
+    location: &'static Location<'static>,
+    #[cfg(feature = "std")]
+    backtrace: Arc<Backtrace>,
+}
+
+#[cfg(feature = "alloc")]
+impl ErrorReport {
+    /// The maximum length in bytes of the `Debug` output of the value in a report.
+    pub const MAX_DEBUG_LEN: usize = 1024;
+
+    /// The name of the type which the value was expected to be.
+    pub fn expected(&self) -> &'static str {
+        self.mismatch.expected
+    }
+
+    /// The name of the type which the value actually was.
+    pub fn actual(&self) -> &'static str {
+        self.mismatch.actual
+    }
+
+    /// A hash of the name of the type which the value was expected to be.
+    ///
+    /// See [`actual_fingerprint`](Self::actual_fingerprint) for details.
+    pub fn expected_fingerprint(&self) -> u64 {
+        fingerprint(self.mismatch.expected)
+    }
+
+    /// A hash of the name of the type which the value actually was.
+    ///
+    /// Unlike a [`TypeId`](core::any::TypeId) or [`ShortTypeId`](crate::ShortTypeId), this
+    /// is the same between compilations, so can be used to group reports from different builds.
+    /// It is the 64-bit FNV-1a hash of the type name, so only changes if the type name does.
+    /// Note that the format of type names isn't guaranteed to be the same between versions
+    /// of the compiler.
+    pub fn actual_fingerprint(&self) -> u64 {
+        fingerprint(self.mismatch.actual)
+    }
+
+    /// The [`Debug`](core::fmt::Debug) output of the value, truncated to at most
+    /// [`MAX_DEBUG_LEN`](Self::MAX_DEBUG_LEN) bytes.
+    pub fn debug(&self) -> &str {
+        &self.debug
+    }
+
+    /// Returns `true` if the [`debug`](Self::debug) output was truncated.
+    pub fn is_debug_truncated(&self) -> bool {
+        self.debug_truncated
+    }
+
+    /// The location in the source code where the downcast failed.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// The backtrace of the failed downcast, if one was captured.
+    ///
+    /// Backtraces are only captured if enabled by the environment variables described in
+    /// the documentation of [`Backtrace`].
+    #[cfg(feature = "std")]
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        match self.backtrace.status() {
+            std::backtrace::BacktraceStatus::Captured => Some(&self.backtrace),
+            _ => None,
+        }
+    }
+}
+
+/// The 64-bit FNV-1a hash of a type name.
+#[cfg(feature = "alloc")]
+fn fingerprint(type_name: &str) -> u64 {
+    let mut hasher = crate::type_id::Fnv1a::default();
+    hasher.write(type_name.as_bytes());
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::TypeMismatch;
+    use crate::AnyDebug;
+    #[cfg(feature = "alloc")]
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn try_downcast_ref_mismatch() {
+        let val = SomeMessage(72);
+        let val: &dyn AnyDebug = &val;
+        assert_eq!(val.try_downcast_ref::<SomeMessage>().unwrap().0, 72);
+        let err = val.try_downcast_ref::<u32>().unwrap_err();
+        assert_eq!(err, TypeMismatch::new::<u32>(val));
+        assert_eq!(err.expected(), "u32");
+        assert!(err.actual().ends_with("SomeMessage"));
+    }
+    #[test]
+    fn try_downcast_mut_mismatch() {
+        let mut val = SomeMessage(73);
+        let val_mut: &mut (dyn AnyDebug + Send) = &mut val;
+        val_mut.try_downcast_mut::<SomeMessage>().unwrap().0 = 74;
+        let err = val_mut.try_downcast_mut::<u32>().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("expected a value of type `u32`"));
+        assert_eq!(val.0, 74);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn downcast_error_display() {
+        let val: Box<dyn AnyDebug + Send> = Box::new(SomeMessage(70));
+        let err = val.downcast_debug::<u32>().unwrap_err();
+        let text = err.to_string();
+        assert!(text.contains("`u32`"));
+        assert!(text.contains("SomeMessage`"));
+        assert!(text.ends_with("SomeMessage(70)"));
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn downcast_error_report() {
+        let val: Box<dyn AnyDebug> = Box::new(SomeMessage(75));
+        let line = line!() + 1;
+        let report = val.downcast_debug::<u32>().unwrap_err().to_report();
+        assert_eq!(report.expected(), "u32");
+        assert!(report.actual().ends_with("SomeMessage"));
+        assert_eq!(report.debug(), "SomeMessage(75)");
+        assert!(!report.is_debug_truncated());
+        assert_eq!(report.location().file(), file!());
+        assert_eq!(report.location().line(), line);
+        // The fingerprints are fixed, so that they can be compared between builds.
+        assert_eq!(report.expected_fingerprint(), 0x4d2b_df19_3e85_26d1);
+        assert_ne!(report.actual_fingerprint(), report.expected_fingerprint());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn downcast_error_report_truncates_at_char_boundary() {
+        use super::ErrorReport;
+
+        // Each `é` is two bytes, and the opening quote is one, so the limit is mid-character.
+        let val: Box<dyn AnyDebug> = Box::new(alloc::string::String::from("é").repeat(600));
+        let report = val.downcast_debug::<u32>().unwrap_err().to_report();
+        assert!(report.is_debug_truncated());
+        assert_eq!(report.debug().len(), ErrorReport::MAX_DEBUG_LEN - 1);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn downcast_debug_success() {
+        let val: Box<dyn AnyDebug> = Box::new(SomeMessage(71));
+        assert_eq!(val.downcast_debug::<SomeMessage>().unwrap().0, 71);
+    }
+}
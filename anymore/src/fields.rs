@@ -0,0 +1,178 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Access to the fields of dynamically typed values.
+
+use core::fmt::{self, Debug};
+use core::iter::FusedIterator;
+
+use crate::AnyDebug;
+
+/// A value whose fields can be accessed individually, such as to show them in a debug overlay.
+///
+/// Each field has a name and a value, which is itself a `dyn AnyDebug`.
+/// The fields of tuple structs are named by their index, such as `"0"`.
+///
+/// If the `derive` feature is enabled, this trait can be implemented using
+/// `#[derive(DebugFields)]`, for structs whose fields all implement `AnyDebug`.
+/// If the `std` feature is enabled, types which implement this trait can be registered with
+/// `registry::register_fields`, so that the fields of a `dyn AnyDebug` value can be accessed.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{AnyDebug, DebugFields};
+///
+/// #[derive(Debug)]
+/// struct Resize { width: u32, height: u32 }
+///
+/// impl DebugFields for Resize {
+///     fn field_count(&self) -> usize {
+///         2
+///     }
+///
+///     fn field(&self, index: usize) -> Option<(&'static str, &dyn AnyDebug)> {
+///         match index {
+///             0 => Some(("width", &self.width)),
+///             1 => Some(("height", &self.height)),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let message: &dyn DebugFields = &Resize { width: 640, height: 480 };
+/// let names: Vec<_> = message.fields().map(|(name, _)| name).collect();
+/// assert_eq!(names, ["width", "height"]);
+/// ```
+pub trait DebugFields: AnyDebug {
+    /// The number of fields.
+    ///
+    /// [`field`](Self::field) must return `Some` for each index less than this count.
+    fn field_count(&self) -> usize;
+
+    /// The name and value of the field at `index`, or `None` if there are not enough fields.
+    fn field(&self, index: usize) -> Option<(&'static str, &dyn AnyDebug)>;
+}
+
+/// An iterator over the names and values of the fields of a [`DebugFields`] value.
+///
+/// This is returned by the `fields` method of `dyn DebugFields`, and can be created for
+/// values of concrete types using [`new`](Self::new).
+#[derive(Clone)]
+pub struct DebugFieldsIter<'a> {
+    value: &'a dyn DebugFields,
+    index: usize,
+}
+
+impl<'a> DebugFieldsIter<'a> {
+    /// Returns an iterator over the names and values of the fields of `value`.
+    pub fn new(value: &'a dyn DebugFields) -> Self {
+        Self { value, index: 0 }
+    }
+}
+
+impl<'a> Iterator for DebugFieldsIter<'a> {
+    type Item = (&'static str, &'a dyn AnyDebug);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.value.field(self.index)?;
+        self.index += 1;
+        Some(field)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.value.field_count().saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for DebugFieldsIter<'_> {}
+
+impl FusedIterator for DebugFieldsIter<'_> {}
+
+impl Debug for DebugFieldsIter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.clone()).finish()
+    }
+}
+
+macro_rules! impl_fields {
+    ($($ty:ty),+) => {
+        $(
+            impl $ty {
+                /// Returns an iterator over the names and values of the fields.
+                pub fn fields(&self) -> DebugFieldsIter<'_> {
+                    DebugFieldsIter::new(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_fields!(
+    dyn DebugFields,
+    dyn DebugFields + Send,
+    dyn DebugFields + Sync,
+    dyn DebugFields + Send + Sync
+);
+
+crate::impl_downcast!(dyn DebugFields);
+crate::impl_downcast!(dyn DebugFields + Send);
+crate::impl_downcast!(dyn DebugFields + Sync);
+crate::impl_downcast!(dyn DebugFields + Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::{DebugFields, DebugFieldsIter};
+    use crate::AnyDebug;
+    use alloc::format;
+
+    #[derive(Debug)]
+    struct SomeMessage {
+        id: u32,
+        label: &'static str,
+    }
+
+    impl DebugFields for SomeMessage {
+        fn field_count(&self) -> usize {
+            2
+        }
+
+        fn field(&self, index: usize) -> Option<(&'static str, &dyn AnyDebug)> {
+            match index {
+                0 => Some(("id", &self.id)),
+                1 => Some(("label", &self.label)),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn debug_fields_iter() {
+        let val = SomeMessage {
+            id: 260,
+            label: "text",
+        };
+        let mut fields = DebugFieldsIter::new(&val);
+        assert_eq!(fields.len(), 2);
+        let (name, id) = fields.next().unwrap();
+        assert_eq!(name, "id");
+        assert_eq!(id.downcast_ref::<u32>(), Some(&260));
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields.next().unwrap().0, "label");
+        assert!(fields.next().is_none());
+    }
+    #[test]
+    fn debug_fields_dyn() {
+        let val: &(dyn DebugFields + Send) = &SomeMessage {
+            id: 261,
+            label: "text",
+        };
+        assert_eq!(
+            format!("{:?}", val.fields()),
+            r#"{"id": 261, "label": "text"}"#
+        );
+        assert_eq!(val.downcast_ref::<SomeMessage>().unwrap().id, 261);
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Deduplication of identical dynamically typed values.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use core::hash::Hasher;
+
+use crate::type_id::Fnv1a;
+use crate::DynHash;
+
+/// A pool which shares a single allocation between values which are equal.
+///
+/// Interning a value returns an `Arc` of a value which is equal to it, reusing the `Arc` of
+/// a previously interned value if there is one.
+/// This reduces the number of allocations for messages which are sent repeatedly with the
+/// same contents, such as timer ticks.
+/// Values of different types are never considered equal, so values of any number of types
+/// can share a pool.
+///
+/// Values are compared using [`DynHash`], so their type must implement `Eq` and `Hash`.
+/// The pool keeps all interned values alive until [`remove_unused`](Self::remove_unused)
+/// is called.
+///
+/// ## Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use anymore::InternPool;
+///
+/// #[derive(Debug, PartialEq, Eq, Hash)]
+/// struct Tick { interval_ms: u32 }
+///
+/// let mut pool = InternPool::new();
+/// let first = pool.intern(Tick { interval_ms: 16 });
+/// let second = pool.intern(Tick { interval_ms: 16 });
+/// assert!(Arc::ptr_eq(&first, &second));
+/// assert_eq!(pool.stats().hits, 1);
+/// ```
+#[derive(Default)]
+pub struct InternPool {
+    /// The interned values, grouped by their hash.
+    buckets: BTreeMap<u64, Vec<Arc<dyn DynHash + Send + Sync>>>,
+    stats: InternStats,
+}
+
+/// How often values interned in an [`InternPool`] were already in the pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InternStats {
+    /// The number of values which were equal to a value already in the pool.
+    pub hits: u64,
+    /// The number of values which were added to the pool.
+    pub misses: u64,
+}
+
+impl InternStats {
+    /// The proportion of interned values which were already in the pool, between 0 and 1.
+    ///
+    /// This is 0 if no values have been interned.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl InternPool {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a shared handle to a value equal to `value`.
+    ///
+    /// If an equal value is already in the pool, `value` is dropped and the handle to that value
+    /// is returned. Otherwise, `value` is added to the pool.
+    pub fn intern<T: DynHash + Send + Sync>(&mut self, value: T) -> Arc<T> {
+        let mut hasher = Fnv1a::default();
+        value.dyn_hash(&mut hasher);
+        let bucket = self.buckets.entry(hasher.finish()).or_default();
+        let existing = bucket.iter().find(|existing| existing.dyn_eq(&value));
+        if let Some(existing) = existing.and_then(|existing| existing.clone().downcast_arc().ok()) {
+            self.stats.hits += 1;
+            return existing;
+        }
+        self.stats.misses += 1;
+        let value = Arc::new(value);
+        bucket.push(value.clone());
+        value
+    }
+
+    /// Removes the values which are only referenced by the pool.
+    pub fn remove_unused(&mut self) {
+        self.buckets.retain(|_, bucket| {
+            bucket.retain(|value| Arc::strong_count(value) > 1);
+            !bucket.is_empty()
+        });
+    }
+
+    /// Returns the number of values in the pool.
+    pub fn len(&self) -> usize {
+        self.buckets.values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if the pool contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Returns how often interned values were already in the pool.
+    pub fn stats(&self) -> InternStats {
+        self.stats
+    }
+}
+
+impl Debug for InternPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InternPool")
+            .field("values", &InternedValues(&self.buckets))
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+/// Formats the values in a pool as a single list.
+struct InternedValues<'a>(&'a BTreeMap<u64, Vec<Arc<dyn DynHash + Send + Sync>>>);
+
+impl Debug for InternedValues<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.values().flatten()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InternPool, InternStats};
+    use alloc::sync::Arc;
+
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn intern_pool_shares_equal_values() {
+        let mut pool = InternPool::new();
+        let first = pool.intern(SomeMessage(240));
+        let second = pool.intern(SomeMessage(240));
+        let other = pool.intern(SomeMessage(241));
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &other));
+        // Values of different types are never equal.
+        assert_eq!(*pool.intern(240_u32), 240);
+        assert_eq!(pool.len(), 3);
+        assert_eq!(pool.stats(), InternStats { hits: 1, misses: 3 });
+        assert_eq!(pool.stats().hit_rate(), 0.25);
+    }
+    #[test]
+    fn intern_pool_remove_unused() {
+        let mut pool = InternPool::new();
+        let kept = pool.intern(SomeMessage(242));
+        drop(pool.intern(SomeMessage(243)));
+        pool.remove_unused();
+        assert_eq!(pool.len(), 1);
+        assert!(Arc::ptr_eq(&kept, &pool.intern(SomeMessage(242))));
+        drop(kept);
+        pool.remove_unused();
+        assert!(pool.is_empty());
+        assert_eq!(InternStats::default().hit_rate(), 0.0);
+    }
+    #[test]
+    fn intern_pool_debug() {
+        let mut pool = InternPool::new();
+        let _val = pool.intern(SomeMessage(244));
+        assert_eq!(
+            alloc::format!("{pool:?}"),
+            "InternPool { values: [SomeMessage(244)], stats: InternStats { hits: 0, misses: 1 } }"
+        );
+    }
+}
@@ -18,7 +18,7 @@
 //!
 //! ## Smart pointers and `dyn AnyDebug`
 //!
-//! When you have `dyn AnyDebug` contained in a smart pointer, such as [`Box`] or
+//! When you have `dyn AnyDebug` contained in a smart pointer, such as [`Box`][alloc::boxed::Box] or
 //! [`Arc`][alloc::sync::Arc],
 //! the [`type_name`][AnyDebug::type_name] method will give the type name of the smart
 //! pointer, rather than the type name of the contained value. This can be avoided by
@@ -26,6 +26,9 @@
 //! object’s type name. This is the [same caveat][core::any#smart-pointers-and-dyn-any]
 //! seen with the`type_id` method on `Any`.
 //!
+//! Alternatively, the [`SharedAny`] and [`LocalShared`] wrappers around `Arc` and `Rc`
+//! respectively report the contained value's type name directly.
+//!
 //! ## Motivation
 //!
 //! In user interface contexts, there is often a need for passing dynamically typed values.
@@ -45,10 +48,25 @@
 //!
 //! The following crate [feature flags](https://doc.rust-lang.org/cargo/reference/features.html#dependency-features) are available:
 //!
-//! - `alloc` (enabled by default): Implement downcasting from [`Box`]es.
+//! - `alloc` (enabled by default): Implement downcasting from [`Box`][alloc::boxed::Box]es and other smart pointers,
+//!   and provide the [`SharedAny`] and [`LocalShared`] wrappers, the [`AnyDebugClone`] trait,
+//!   the [`AnyArgs`] argument list, the [`map::AnyMap`] which stores one value of each type,
+//!   the [`Promoter`] for recovering `AnyDebug` values from `dyn Any` values,
+//!   and the [`InternPool`] for sharing equal values.
+//!   Also provide the `message` and `dispatch` modules, for dynamically typed messages.
 //!   If this feature is not enabled, Anymore can be used in contexts without an allocator enabled.
-//! - `type_name` (enabled by default): Provide the `type_name` function on `AnyDebug`, which gives the type's name.
+//! - `type_name` (enabled by default): Provide the `type_name` function on `AnyDebug`, which gives the type's name,
+//!   and the error types and [`downcast_tuple`] function which report it.
 //!   Most users should leave this enabled, as the costs of this method existing are expected to be negligible.
+//! - `std`: Provide `map::ConcurrentAnyMap`, which needs the standard library for locking.
+//!   Provide the `registry` module, a global registry of types.
+//!   Provide `PanicPayload` and `catch_panic`, for inspecting the payloads of panics.
+//!   Also capture backtraces in `DowncastError`, for its `ErrorReport`.
+//!   With debug assertions enabled, also warn when the crate's boxing helpers erase an already erased value.
+//!   This feature also enables `alloc`.
+//! - `testkit`: Provide the `testkit` module, with conformance checks for downstream payloads, stores and dispatchers.
+//! - `derive`: Provide the `DebugAny` derive macro, which implements `Debug` for structs containing fields
+//!   which don't implement `Debug`, and the `DebugFields` derive macro.
 // LINEBENDER LINT SET - lib.rs - v3
 // See https://linebender.org/wiki/canonical-lints/
 // These lints shouldn't apply to examples or tests.
@@ -66,15 +84,105 @@ use core::fmt::Debug;
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod any_error;
+#[cfg(feature = "alloc")]
+mod args;
+#[cfg(feature = "alloc")]
+mod clone;
+mod cmp;
+#[cfg(feature = "alloc")]
+pub mod dispatch;
+mod display;
+#[cfg(feature = "type_name")]
+mod error;
+mod fields;
+#[cfg(feature = "alloc")]
+mod intern;
+mod macros;
+#[cfg(feature = "alloc")]
+pub mod map;
+#[cfg(feature = "alloc")]
+pub mod message;
+mod opaque;
+#[cfg(feature = "std")]
+mod panic;
+mod payload;
+#[cfg(feature = "alloc")]
+mod promote;
+mod provide;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "alloc")]
+mod shared;
+mod short_name;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+#[cfg(feature = "type_name")]
+mod tuple;
+mod type_id;
+
+pub use any_error::AnyDebugError;
+#[cfg(feature = "derive")]
+pub use anymore_derive::{DebugAny, DebugFields};
 #[cfg(feature = "alloc")]
-use alloc::boxed::Box;
+pub use args::AnyArgs;
+#[cfg(feature = "alloc")]
+pub use clone::AnyDebugClone;
+pub use cmp::{cmp_by_type, DynHash, DynOrd, DynPartialEq};
+pub use display::{AnyDebugDisplay, AnyDisplay};
+#[cfg(feature = "type_name")]
+pub use error::{ArgumentError, TypeMismatch};
+#[cfg(all(feature = "alloc", feature = "type_name"))]
+pub use error::{DowncastError, ErrorReport};
+pub use fields::{DebugFields, DebugFieldsIter};
+#[cfg(feature = "alloc")]
+pub use intern::{InternPool, InternStats};
+pub use opaque::Opaque;
+#[cfg(feature = "std")]
+pub use panic::{catch_panic, PanicPayload};
+pub use payload::Payload;
+#[cfg(feature = "alloc")]
+pub use promote::Promoter;
+pub use provide::{Provider, Request};
+#[cfg(feature = "alloc")]
+pub use shared::{LocalShared, SharedAny};
+pub use short_name::{short_type_name, ShortTypeName};
+#[cfg(feature = "type_name")]
+pub use tuple::{downcast_tuple, DowncastTuple};
+pub use type_id::ShortTypeId;
 
 /// A trait to implement dynamic typing.
 ///
 /// This trait is the same as the standard library [`Any`] trait,
 /// except that it can be debug printed.
 ///
+/// As `Any` is a supertrait, its [`type_id`](Any::type_id) method can be called directly on
+/// a `dyn AnyDebug` value, without converting it into a `dyn Any` first.
+/// The same [smart pointer caveat](crate#smart-pointers-and-dyn-anydebug) applies as for
+/// [`type_name`](AnyDebug::type_name).
+///
 /// See also the [crate level documentation](crate) for more details.
+///
+/// Using a type which doesn't implement `Debug` as an `AnyDebug` value is an error, which
+/// suggests deriving `Debug` or wrapping the value in an [`Opaque`]:
+///
+/// ```compile_fail,E0277
+/// use anymore::AnyDebug;
+///
+/// struct NotDebug;
+///
+/// let value: &dyn AnyDebug = &NotDebug;
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be used as a dynamically typed `AnyDebug` value",
+    label = "`{Self}` does not implement `AnyDebug`",
+    note = "`AnyDebug` is implemented for all `'static` types which implement `Debug`, so `{Self}` most likely needs `#[derive(Debug)]`",
+    note = "if `{Self}` is from another crate and can't implement `Debug`, wrap it in `anymore::Opaque`",
+    note = "use `anymore::assert_any_debug!({Self})` to find out which bound is not satisfied"
+)]
 pub trait AnyDebug: Any + Debug {
     /// Returns the [`type_name`](core::any::type_name) of this value's concrete type.
     ///
@@ -97,129 +205,257 @@ impl<T: Any + Debug> AnyDebug for T {
     }
 }
 
-impl dyn AnyDebug {
-    /// Returns some shared reference to the inner value if it is of type `T`, or
-    /// `None` if it isn't.
+impl_downcast!(dyn AnyDebug);
+impl_downcast!(dyn AnyDebug + Send);
+impl_downcast!(dyn AnyDebug + Sync);
+impl_downcast!(dyn AnyDebug + Send + Sync);
+
+/// Implementation details of the macros exported by this crate.
+#[doc(hidden)]
+pub mod __private {
+    /// Support for the code generated by `#[derive(DebugAny)]`.
     ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn downcast_ref<T: AnyDebug>(&self) -> Option<&T> {
-        (self as &dyn Any).downcast_ref::<T>()
+    /// This uses "autoref specialization": the `maybe_debug` method of `ViaDebug` is found
+    /// before the method of `ViaTypeName` if the field implements `Debug`, as its receiver
+    /// has one more reference.
+    #[cfg(feature = "derive")]
+    pub mod debug_any {
+        use core::fmt::{self, Debug};
+
+        /// A reference to a field, which might implement `Debug`.
+        #[expect(
+            missing_debug_implementations,
+            reason = "Only used to select how the field is formatted"
+        )]
+        pub struct MaybeDebug<'a, T>(pub &'a T);
+
+        /// The `Debug` output of a field, or the name of its type.
+        pub enum DebugOrTypeName<'a> {
+            Debug(&'a dyn Debug),
+            TypeName(&'static str),
+        }
+
+        impl Debug for DebugOrTypeName<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    Self::Debug(value) => value.fmt(f),
+                    Self::TypeName(name) => f.write_str(name),
+                }
+            }
+        }
+
+        /// Formats fields which implement `Debug` using their `Debug` implementation.
+        pub trait ViaDebug<'a> {
+            fn maybe_debug(&self) -> DebugOrTypeName<'a>;
+        }
+
+        impl<'a, T: Debug> ViaDebug<'a> for &MaybeDebug<'a, T> {
+            fn maybe_debug(&self) -> DebugOrTypeName<'a> {
+                DebugOrTypeName::Debug(self.0)
+            }
+        }
+
+        /// Formats fields which don't implement `Debug` as the name of their type.
+        pub trait ViaTypeName<'a> {
+            fn maybe_debug(&self) -> DebugOrTypeName<'a>;
+        }
+
+        impl<'a, T> ViaTypeName<'a> for MaybeDebug<'a, T> {
+            fn maybe_debug(&self) -> DebugOrTypeName<'a> {
+                DebugOrTypeName::TypeName(core::any::type_name::<T>())
+            }
+        }
     }
 
-    /// Returns some exclusive reference to the inner value if it is of type `T`, or
-    /// `None` if it isn't.
+    #[cfg(feature = "alloc")]
+    pub use alloc::{boxed::Box, rc::Rc, sync::Arc};
+
+    use core::fmt::{self, Debug, Display};
+
+    use crate::{AnyDebug, AnyDisplay};
+
+    /// How the values behind the trait objects of `impl_downcast!` are described in errors
+    /// and panic messages.
     ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn downcast_mut<T: AnyDebug>(&mut self) -> Option<&mut T> {
-        (self as &mut dyn Any).downcast_mut::<T>()
+    /// This is implemented for all `AnyDebug` values, and for `dyn AnyDisplay`.
+    pub trait Describe {
+        /// The name of the concrete type of the value.
+        #[cfg(feature = "type_name")]
+        fn describe_type_name(&self) -> &'static str;
+
+        /// Formats the value, using its `Debug` or `Display` implementation.
+        fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
     }
 
-    /// Access the actual type of this [`AnyDebug`].
-    ///
-    /// Forwards to the method defined on the type [`Box<dyn Any>`].
-    ///
-    /// ## Errors
-    ///
-    /// If the message contained within `self` is not of type `T`, returns `self`.
-    #[cfg(feature = "alloc")]
-    pub fn downcast<T: AnyDebug>(self: Box<Self>) -> Result<Box<T>, Box<Self>> {
-        if self.is::<T>() {
-            Ok((self as Box<dyn Any>).downcast::<T>().unwrap())
-        } else {
-            Err(self)
+    impl<T: ?Sized + AnyDebug> Describe for T {
+        #[cfg(feature = "type_name")]
+        fn describe_type_name(&self) -> &'static str {
+            AnyDebug::type_name(self)
+        }
+
+        fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Debug::fmt(self, f)
         }
     }
 
-    /// Returns `true` if the inner type is the same as `T`.
-    ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn is<T: AnyDebug>(&self) -> bool {
-        let this: &dyn Any = self;
-        this.is::<T>()
+    macro_rules! impl_describe_display {
+        ($($ty:ty),+) => {
+            $(
+                impl Describe for $ty {
+                    #[cfg(feature = "type_name")]
+                    fn describe_type_name(&self) -> &'static str {
+                        AnyDisplay::type_name(self)
+                    }
+
+                    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        Display::fmt(self, f)
+                    }
+                }
+            )+
+        };
     }
-}
 
-impl dyn AnyDebug + Send {
-    /// Returns some shared reference to the inner value if it is of type `T`, or
-    /// `None` if it isn't.
-    ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn downcast_ref<T: AnyDebug>(&self) -> Option<&T> {
-        (self as &dyn Any).downcast_ref::<T>()
+    impl_describe_display!(
+        dyn AnyDisplay,
+        dyn AnyDisplay + Send,
+        dyn AnyDisplay + Sync,
+        dyn AnyDisplay + Send + Sync
+    );
+
+    /// Formats a value using its [`Describe`] implementation.
+    struct Described<'a, V: ?Sized>(&'a V);
+
+    impl<V: ?Sized + Describe> Debug for Described<'_, V> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.describe(f)
+        }
     }
 
-    /// Returns some exclusive reference to the inner value if it is of type `T`, or
-    /// `None` if it isn't.
-    ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn downcast_mut<T: AnyDebug>(&mut self) -> Option<&mut T> {
-        (self as &mut dyn Any).downcast_mut::<T>()
+    /// Panics because `value` was expected to be of type `T`, but wasn't.
+    #[cold]
+    #[inline(never)]
+    #[track_caller]
+    pub fn unexpected_type<T: ?Sized>(value: &(impl Describe + ?Sized)) -> ! {
+        let described = Described(value);
+        #[cfg(feature = "type_name")]
+        panic!("{}: {described:?}", type_mismatch::<T, _>(value));
+        #[cfg(not(feature = "type_name"))]
+        panic!(
+            "expected a value of type `{}`, but got: {described:?}",
+            core::any::type_name::<T>()
+        );
     }
 
-    /// Access the actual type of this [`AnyDebug`].
-    ///
-    /// Forwards to the method defined on the type [`Box<dyn Any>`].
-    ///
-    /// ## Errors
-    ///
-    /// If the message contained within `self` is not of type `T`, returns `self`.
-    #[cfg(feature = "alloc")]
-    pub fn downcast<T: AnyDebug>(self: Box<Self>) -> Result<Box<T>, Box<Self>> {
-        if self.is::<T>() {
-            Ok((self as Box<dyn Any>).downcast::<T>().unwrap())
-        } else {
-            Err(self)
-        }
+    /// The error for `value` not being of the type `T`.
+    #[cfg(feature = "type_name")]
+    pub fn type_mismatch<T: ?Sized, V: Describe + ?Sized>(value: &V) -> crate::TypeMismatch {
+        crate::TypeMismatch::from_names(core::any::type_name::<T>(), value.describe_type_name())
     }
 
-    /// Returns `true` if the inner type is the same as `T`.
-    ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn is<T: AnyDebug>(&self) -> bool {
-        let this: &dyn Any = self;
-        this.is::<T>()
+    /// The error for a failed downcast of `value` to the type `T`.
+    #[cfg(all(feature = "alloc", feature = "type_name"))]
+    #[track_caller]
+    pub fn downcast_error<T: ?Sized, V: Describe + ?Sized>(value: &V) -> crate::DowncastError {
+        crate::DowncastError::from_parts(
+            type_mismatch::<T, V>(value),
+            alloc::format!("{:?}", Described(value)),
+        )
     }
-}
 
-impl dyn AnyDebug + Send + Sync {
-    /// Returns some shared reference to the inner value if it is of type `T`, or
-    /// `None` if it isn't.
+    /// The trait objects of `AnyDebug` which boxes passed to `Opaque::downcast` can contain.
     ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn downcast_ref<T: AnyDebug>(&self) -> Option<&T> {
-        (self as &dyn Any).downcast_ref::<T>()
+    /// This is implemented for `dyn AnyDebug` and its combinations with `Send` and `Sync`.
+    #[cfg(feature = "alloc")]
+    pub trait ErasedAnyDebug: AnyDebug {
+        /// Converts the box into a `Box<dyn Any>`, for downcasting.
+        fn into_any(self: Box<Self>) -> Box<dyn core::any::Any>;
     }
 
-    /// Returns some exclusive reference to the inner value if it is of type `T`, or
-    /// `None` if it isn't.
-    ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn downcast_mut<T: AnyDebug>(&mut self) -> Option<&mut T> {
-        (self as &mut dyn Any).downcast_mut::<T>()
+    #[cfg(feature = "alloc")]
+    macro_rules! impl_erased_any_debug {
+        ($($ty:ty),+) => {
+            $(
+                impl ErasedAnyDebug for $ty {
+                    fn into_any(self: Box<Self>) -> Box<dyn core::any::Any> {
+                        self
+                    }
+                }
+            )+
+        };
     }
 
-    /// Access the actual type of this [`AnyDebug`].
-    ///
-    /// Forwards to the method defined on the type [`Box<dyn Any>`].
-    ///
-    /// ## Errors
+    #[cfg(feature = "alloc")]
+    impl_erased_any_debug!(
+        dyn AnyDebug,
+        dyn AnyDebug + Send,
+        dyn AnyDebug + Sync,
+        dyn AnyDebug + Send + Sync
+    );
+
+    /// Warns that `value` is itself a type-erased container, if it is, and debug assertions
+    /// and the `std` feature are enabled.
     ///
-    /// If the message contained within `self` is not of type `T`, returns `self`.
+    /// This is called by the crate's helpers which box or share values, as downcasting the
+    /// resulting value to the type of the value inside the container will fail.
     #[cfg(feature = "alloc")]
-    pub fn downcast<T: AnyDebug>(self: Box<Self>) -> Result<Box<T>, Box<Self>> {
-        if self.is::<T>() {
-            Ok((self as Box<dyn Any>).downcast::<T>().unwrap())
-        } else {
-            Err(self)
+    #[inline]
+    #[track_caller]
+    pub fn warn_if_nested(value: &dyn AnyDebug) {
+        #[cfg(all(debug_assertions, feature = "std"))]
+        if is_erased_container(value) {
+            #[expect(
+                clippy::print_stderr,
+                reason = "This is a debugging aid, and the crate has no other way to report it"
+            )]
+            {
+                std::eprintln!(
+                    "warning: a value of type `{:?}`, which is already type-erased, was erased \
+                     again at {}; downcasting it to the type of the value inside will fail",
+                    crate::map::TypeLabel(value),
+                    core::panic::Location::caller(),
+                );
+            }
         }
+        #[cfg(not(all(debug_assertions, feature = "std")))]
+        let _ = value;
     }
 
-    /// Returns `true` if the inner type is the same as `T`.
-    ///
-    /// Forwards to the method defined on the type `dyn Any`.
-    pub fn is<T: AnyDebug>(&self) -> bool {
-        let this: &dyn Any = self;
-        this.is::<T>()
+    /// Whether `value` is a box of a trait object of one of the listed traits, or one of their
+    /// combinations with `Send` and `Sync`.
+    #[cfg(feature = "alloc")]
+    macro_rules! is_boxed_trait_object {
+        ($value:ident: $($trait:ident),+ $(,)?) => {
+            false $(
+                || $value.is::<Box<dyn $trait>>()
+                || $value.is::<Box<dyn $trait + Send>>()
+                || $value.is::<Box<dyn $trait + Sync>>()
+                || $value.is::<Box<dyn $trait + Send + Sync>>()
+            )+
+        };
+    }
+
+    /// Whether `value` is one of the containers of `dyn AnyDebug` which this crate knows about.
+    #[cfg(feature = "alloc")]
+    pub fn is_erased_container(value: &dyn core::any::Any) -> bool {
+        use crate::{
+            AnyDebugClone, AnyDebugDisplay, AnyDebugError, DynHash, DynOrd, DynPartialEq,
+            LocalShared, SharedAny,
+        };
+
+        // Every trait in this crate which uses `impl_downcast!` must be listed here, as boxes
+        // of its trait objects implement `AnyDebug`, so can themselves be erased.
+        is_boxed_trait_object!(value: AnyDebug, AnyDebugClone, AnyDebugDisplay, AnyDebugError, AnyDisplay, DynHash, DynOrd, DynPartialEq)
+            || value.is::<Arc<dyn AnyDebug>>()
+            || value.is::<Arc<dyn AnyDebug + Send>>()
+            || value.is::<Arc<dyn AnyDebug + Sync>>()
+            || value.is::<Arc<dyn AnyDebug + Send + Sync>>()
+            || value.is::<Rc<dyn AnyDebug>>()
+            || value.is::<Rc<dyn AnyDebug + Send>>()
+            || value.is::<Rc<dyn AnyDebug + Sync>>()
+            || value.is::<Rc<dyn AnyDebug + Send + Sync>>()
+            || value.is::<SharedAny>()
+            || value.is::<LocalShared>()
+            || value.is::<crate::message::Message>()
     }
 }
 
@@ -228,6 +464,9 @@ mod tests {
     extern crate alloc;
     use crate::AnyDebug;
     use alloc::{boxed::Box, format};
+    #[cfg(feature = "alloc")]
+    use alloc::{rc::Rc, sync::Arc};
+    use core::any::TypeId;
 
     #[derive(Debug)]
     struct SomeMessage(u32);
@@ -273,6 +512,18 @@ mod tests {
         assert!(!val.is::<u32>());
     }
     #[test]
+    fn any_debug_normal_type_id() {
+        let val = SomeMessage(38);
+        let val: &dyn AnyDebug = &val;
+        assert_eq!(val.type_id(), TypeId::of::<SomeMessage>());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_send_sync_type_id_boxed() {
+        let val: Box<dyn AnyDebug + Send + Sync> = Box::new(SomeMessage(39));
+        assert_eq!(val.type_id(), TypeId::of::<SomeMessage>());
+    }
+    #[test]
     fn any_debug_normal_downcast_ref() {
         let val = SomeMessage(11);
         let val: &dyn AnyDebug = &val;
@@ -296,6 +547,50 @@ mod tests {
         let val = val.downcast::<SomeMessage>().unwrap();
         assert_eq!(val.0, 14);
     }
+    #[test]
+    fn any_debug_normal_expect_downcast_ref() {
+        let val = SomeMessage(17);
+        let val: &dyn AnyDebug = &val;
+        assert_eq!(val.expect_downcast_ref::<SomeMessage>().0, 17);
+    }
+    #[test]
+    #[should_panic(expected = "SomeMessage(18)")]
+    fn any_debug_normal_expect_downcast_ref_panics() {
+        let val = SomeMessage(18);
+        let val: &dyn AnyDebug = &val;
+        val.expect_downcast_ref::<u32>();
+    }
+    #[test]
+    #[should_panic(expected = "SomeMessage(19)")]
+    fn any_debug_normal_expect_downcast_mut_panics() {
+        let mut val = SomeMessage(19);
+        let val: &mut dyn AnyDebug = &mut val;
+        val.expect_downcast_mut::<u32>();
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[should_panic(expected = "expected a value of type `u32`")]
+    fn any_debug_send_expect_downcast_panics() {
+        let val: Box<dyn AnyDebug + Send> = Box::new(SomeMessage(26));
+        val.expect_downcast::<u32>();
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_normal_downcast_rc() {
+        let val = SomeMessage(16);
+        let val: Rc<dyn AnyDebug> = Rc::new(val);
+        let val = val.downcast_rc::<u32>().unwrap_err();
+        let val = val.downcast_rc::<SomeMessage>().unwrap();
+        assert_eq!(val.0, 16);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_normal_is_nested_box() {
+        let val: Box<dyn AnyDebug> = Box::new(SomeMessage(15));
+        assert!(!val.is_nested_box());
+        let val: Box<dyn AnyDebug> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
 
     #[test]
     fn any_debug_send_is() {
@@ -322,11 +617,59 @@ mod tests {
     #[cfg(feature = "alloc")]
     fn any_debug_send_downcast() {
         let val = SomeMessage(24);
-        let val: Box<(dyn AnyDebug + Send)> = Box::new(val);
+        let val: Box<dyn AnyDebug + Send> = Box::new(val);
         let val = val.downcast::<u32>().unwrap_err();
         let val = val.downcast::<SomeMessage>().unwrap();
         assert_eq!(val.0, 24);
     }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_send_is_nested_box() {
+        let val: Box<dyn AnyDebug + Send> = Box::new(SomeMessage(25));
+        assert!(!val.is_nested_box());
+        let val: Box<dyn AnyDebug + Send> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+
+    #[test]
+    fn any_debug_sync_is() {
+        let val = SomeMessage(60);
+        let val: &(dyn AnyDebug + Sync) = &val;
+        assert!(val.is::<SomeMessage>());
+        assert!(!val.is::<u32>());
+    }
+    #[test]
+    fn any_debug_sync_downcast_ref() {
+        let val = SomeMessage(61);
+        let val: &(dyn AnyDebug + Sync) = &val;
+        assert_eq!(val.downcast_ref::<SomeMessage>().unwrap().0, 61);
+    }
+    #[test]
+    fn any_debug_sync_downcast_mut() {
+        let mut val = SomeMessage(62);
+        let val_mut: &mut (dyn AnyDebug + Sync) = &mut val;
+        val_mut.downcast_mut::<SomeMessage>().unwrap().0 = 63;
+        assert!(val_mut.downcast_mut::<u32>().is_none());
+        assert_eq!(val.0, 63);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_sync_downcast() {
+        let val = SomeMessage(64);
+        let val: Box<dyn AnyDebug + Sync> = Box::new(val);
+        let val = val.downcast::<u32>().unwrap_err();
+        let val = val.downcast::<SomeMessage>().unwrap();
+        assert_eq!(val.0, 64);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_sync_is_nested_box() {
+        let val: Box<dyn AnyDebug + Sync> = Box::new(SomeMessage(65));
+        assert!(!val.is_nested_box());
+        let val: Box<dyn AnyDebug + Sync> =
+            Box::new(Box::new(SomeMessage(66)) as Box<dyn AnyDebug + Sync>);
+        assert!(val.is_nested_box());
+    }
 
     #[test]
     fn any_debug_send_sync_is() {
@@ -353,9 +696,141 @@ mod tests {
     #[cfg(feature = "alloc")]
     fn any_debug_send_sync_downcast() {
         let val = SomeMessage(34);
-        let val: Box<(dyn AnyDebug + Send + Sync)> = Box::new(val);
+        let val: Box<dyn AnyDebug + Send + Sync> = Box::new(val);
         let val = val.downcast::<u32>().unwrap_err();
         let val = val.downcast::<SomeMessage>().unwrap();
         assert_eq!(val.0, 34);
     }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_send_sync_downcast_arc() {
+        let val = SomeMessage(37);
+        let val: Arc<dyn AnyDebug + Send + Sync> = Arc::new(val);
+        let val = val.downcast_arc::<u32>().unwrap_err();
+        let val = val.downcast_arc::<SomeMessage>().unwrap();
+        assert_eq!(val.0, 37);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn any_debug_send_sync_is_nested_box() {
+        let val: Box<dyn AnyDebug + Send + Sync> = Box::new(SomeMessage(35));
+        assert!(!val.is_nested_box());
+        let val: Box<dyn AnyDebug + Send + Sync> = Box::new(crate::SharedAny::new(SomeMessage(36)));
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn arc_normal_is_nested_box() {
+        let val: Arc<dyn AnyDebug> = Arc::new(SomeMessage(50));
+        let val: Box<dyn AnyDebug> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn arc_send_is_nested_box() {
+        let val: Arc<dyn AnyDebug + Send> = Arc::new(SomeMessage(51));
+        let val: Box<dyn AnyDebug> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn arc_sync_is_nested_box() {
+        let val: Arc<dyn AnyDebug + Sync> = Arc::new(SomeMessage(52));
+        let val: Box<dyn AnyDebug> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn arc_send_sync_is_nested_box() {
+        let val: Arc<dyn AnyDebug + Send + Sync> = Arc::new(SomeMessage(53));
+        let val: Box<dyn AnyDebug + Send + Sync> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn rc_normal_is_nested_box() {
+        let val: Rc<dyn AnyDebug> = Rc::new(SomeMessage(54));
+        let val: Box<dyn AnyDebug> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn rc_send_is_nested_box() {
+        let val: Rc<dyn AnyDebug + Send> = Rc::new(SomeMessage(55));
+        let val: Box<dyn AnyDebug> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn rc_sync_is_nested_box() {
+        let val: Rc<dyn AnyDebug + Sync> = Rc::new(SomeMessage(56));
+        let val: Box<dyn AnyDebug> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn rc_send_sync_is_nested_box() {
+        let val: Rc<dyn AnyDebug + Send + Sync> = Rc::new(SomeMessage(57));
+        let val: Box<dyn AnyDebug> = Box::new(val);
+        assert!(val.is_nested_box());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn anydebug_macro() {
+        let val = crate::anydebug!(SomeMessage(45));
+        assert_eq!(val.downcast_ref::<SomeMessage>().unwrap().0, 45);
+        let val: Box<dyn AnyDebug + Send> = crate::anydebug!(send: SomeMessage(46));
+        assert!(!val.is_nested_box());
+        let _: Box<dyn AnyDebug + Sync> = crate::anydebug!(sync: SomeMessage(47));
+        let val = crate::anydebug!(send + sync: SomeMessage(48));
+        let val: alloc::sync::Arc<dyn AnyDebug + Send + Sync> = val.into();
+        assert_eq!(val.downcast_arc::<SomeMessage>().unwrap().0, 48);
+        // A variable with the same name as an auto trait is boxed as a value.
+        let send = SomeMessage(49);
+        assert!(crate::anydebug!(send).is::<SomeMessage>());
+    }
+    #[test]
+    fn match_downcast_arms() {
+        fn handle(val: &dyn AnyDebug) -> u32 {
+            crate::match_downcast!(val,
+                message: SomeMessage => message.0,
+                num: u32 => *num + 1,
+                other => {
+                    assert!(other.is::<u8>(), "only `u8` values should reach the fallback");
+                    0
+                },
+            )
+        }
+        assert_eq!(handle(&SomeMessage(40)), 40);
+        assert_eq!(handle(&40_u32), 41);
+        assert_eq!(handle(&42_u8), 0);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn match_downcast_boxed_fallback() {
+        let val: Box<dyn AnyDebug + Send> = Box::new(43_u8);
+        let debug = crate::match_downcast!(val,
+            _message: SomeMessage => unreachable!(),
+            other => alloc::format!("{other:?}"),
+        );
+        assert_eq!(debug, "43");
+        // The value is only borrowed.
+        assert!(val.is::<u8>());
+    }
+    #[test]
+    #[cfg(all(feature = "alloc", feature = "type_name"))]
+    fn match_downcast_fallback_type_name() {
+        let val: Box<dyn AnyDebug> = Box::new(SomeMessage(44));
+        let name = crate::match_downcast!(val,
+            _num: u32 => unreachable!(),
+            other => other.type_name(),
+        );
+        assert!(
+            name.ends_with("SomeMessage"),
+            "{name} should be the type of the boxed value"
+        );
+    }
+
+    crate::assert_any_debug!(SomeMessage);
+    crate::assert_any_debug!(SomeMessage: Send + Sync);
 }
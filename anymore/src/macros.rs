@@ -0,0 +1,522 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Macros exported by Anymore.
+
+/// Asserts at compile time that a type can be used as an [`AnyDebug`](crate::AnyDebug) value.
+///
+/// Using a type which doesn't meet the bounds of `AnyDebug` where one is expected produces
+/// an error such as "the trait bound `X: AnyDebug` is not satisfied", which doesn't say which
+/// of the supertraits is missing.
+/// This macro checks each bound separately, so that the error points at the bound which failed.
+///
+/// The auto traits which the type must also implement can be listed after a colon, for
+/// types which are intended to be used as `dyn AnyDebug + Send` or `dyn AnyDebug + Send + Sync`.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::assert_any_debug;
+///
+/// #[derive(Debug)]
+/// struct ButtonPressed(u32);
+///
+/// assert_any_debug!(ButtonPressed);
+/// assert_any_debug!(ButtonPressed: Send + Sync);
+/// ```
+///
+/// A type which doesn't implement `Debug` will fail to compile:
+///
+/// ```compile_fail
+/// struct NotDebug;
+///
+/// anymore::assert_any_debug!(NotDebug);
+/// ```
+///
+/// As will a type which is missing one of the requested auto traits:
+///
+/// ```compile_fail
+/// use std::rc::Rc;
+///
+/// #[derive(Debug)]
+/// struct Shared(Rc<u32>);
+///
+/// anymore::assert_any_debug!(Shared: Send);
+/// ```
+///
+/// As will an unsized type, because `AnyDebug` is only implemented for sized types:
+///
+/// ```compile_fail
+/// anymore::assert_any_debug!(str);
+/// ```
+#[macro_export]
+macro_rules! assert_any_debug {
+    (@auto_trait $ty:ty: $auto:ident) => {{
+        fn assert_implements_auto_trait<T: ?Sized + $auto>() {}
+        assert_implements_auto_trait::<$ty>();
+    }};
+    ($ty:ty $(: $first:ident $(+ $rest:ident)*)? $(,)?) => {
+        const _: fn() = || {
+            fn assert_implements_debug<T: ?Sized + ::core::fmt::Debug>() {}
+            fn assert_is_static<T: ?Sized + 'static>() {}
+            fn assert_is_sized<T>() {}
+            assert_implements_debug::<$ty>();
+            assert_is_static::<$ty>();
+            assert_is_sized::<$ty>();
+            $(
+                $crate::assert_any_debug!(@auto_trait $ty: $first);
+                $($crate::assert_any_debug!(@auto_trait $ty: $rest);)*
+            )?
+        };
+    };
+}
+
+/// Implements the downcasting methods of `dyn AnyDebug` for a trait object type.
+///
+/// Anymore provides methods such as [`downcast_ref`](crate::AnyDebug#method.downcast_ref)
+/// and [`is`](crate::AnyDebug#method.is) on `dyn AnyDebug`, and its combinations with
+/// `Send` and `Sync`, in the same way that the standard library does for `dyn Any`.
+/// This macro generates the same set of methods for trait objects of your own traits
+/// which have `AnyDebug` as a supertrait.
+///
+/// The generated methods are:
+///
+/// - `is`, `downcast_ref` and `downcast_mut`.
+/// - `try_downcast_ref` and `try_downcast_mut`, returning a [`TypeMismatch`](crate::TypeMismatch)
+///   on failure (if the `type_name` feature of Anymore is enabled).
+/// - `expect_downcast_ref` and `expect_downcast_mut`, which panic with the `Debug` output of
+///   the value if it is of the wrong type.
+/// - `downcast` and `expect_downcast`, from a `Box<Self>` (if the `alloc` feature of Anymore
+///   is enabled).
+/// - `downcast_rc`, from an `Rc<Self>`, for the trait object without auto traits
+///   (if the `alloc` feature of Anymore is enabled).
+/// - `downcast_arc`, from an `Arc<Self>`, for the trait object with `Send + Sync`
+///   (if the `alloc` feature of Anymore is enabled).
+/// - `downcast_debug`, from a `Box<Self>`, returning a [`DowncastError`](crate::DowncastError)
+///   on failure (if the `alloc` and `type_name` features of Anymore are enabled).
+/// - `is_nested_box` (if the `alloc` feature of Anymore is enabled).
+///
+/// As these are inherent methods, this macro must be used in the crate which defines the trait.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyDebug;
+///
+/// /// A message which can be sent to a widget.
+/// trait WidgetMessage: AnyDebug {}
+///
+/// #[derive(Debug)]
+/// struct Click;
+/// impl WidgetMessage for Click {}
+///
+/// anymore::impl_downcast!(dyn WidgetMessage);
+/// anymore::impl_downcast!(dyn WidgetMessage + Send);
+///
+/// let message: &(dyn WidgetMessage + Send) = &Click;
+/// assert!(message.is::<Click>());
+/// let click: &Click = message.downcast_ref().unwrap();
+/// ```
+#[macro_export]
+macro_rules! impl_downcast {
+    (dyn $trait:ident $(+ $auto:ident)*) => {
+        $crate::impl_downcast!(@bound $crate::AnyDebug, "`Debug` output"; dyn $trait $(+ $auto)*);
+    };
+    // The methods are generic over types implementing `$bound`, and `$output` describes how
+    // values are formatted in errors and panic messages.
+    (@bound $bound:path, $output:literal; dyn $trait:ident) => {
+        impl dyn $trait {
+            $crate::impl_downcast!(@methods $bound, $output);
+            $crate::__if_alloc! {
+                /// Access the actual type of this value behind an `Rc`.
+                ///
+                /// Forwards to the method defined on the type `Rc<dyn Any>`.
+                ///
+                /// ## Errors
+                ///
+                /// If the message contained within `self` is not of type `T`, returns `self`.
+                pub fn downcast_rc<T: $bound>(
+                    self: $crate::__private::Rc<Self>,
+                ) -> ::core::result::Result<$crate::__private::Rc<T>, $crate::__private::Rc<Self>>
+                {
+                    if self.is::<T>() {
+                        ::core::result::Result::Ok(
+                            (self as $crate::__private::Rc<dyn ::core::any::Any>)
+                                .downcast::<T>()
+                                .unwrap(),
+                        )
+                    } else {
+                        ::core::result::Result::Err(self)
+                    }
+                }
+            }
+        }
+    };
+    (@bound $bound:path, $output:literal; dyn $trait:ident + Send + Sync) => {
+        impl dyn $trait + Send + Sync {
+            $crate::impl_downcast!(@methods $bound, $output);
+            $crate::__if_alloc! {
+                /// Access the actual type of this value behind an `Arc`.
+                ///
+                /// Forwards to the method defined on the type `Arc<dyn Any + Send + Sync>`.
+                ///
+                /// ## Errors
+                ///
+                /// If the message contained within `self` is not of type `T`, returns `self`.
+                pub fn downcast_arc<T: $bound + Send + Sync>(
+                    self: $crate::__private::Arc<Self>,
+                ) -> ::core::result::Result<$crate::__private::Arc<T>, $crate::__private::Arc<Self>>
+                {
+                    if self.is::<T>() {
+                        ::core::result::Result::Ok(
+                            (self as $crate::__private::Arc<dyn ::core::any::Any + Send + Sync>)
+                                .downcast::<T>()
+                                .unwrap(),
+                        )
+                    } else {
+                        ::core::result::Result::Err(self)
+                    }
+                }
+            }
+        }
+    };
+    (@bound $bound:path, $output:literal; dyn $trait:ident + Sync + Send) => {
+        $crate::impl_downcast!(@bound $bound, $output; dyn $trait + Send + Sync);
+    };
+    (@bound $bound:path, $output:literal; dyn $trait:ident + Send) => {
+        impl dyn $trait + Send {
+            $crate::impl_downcast!(@methods $bound, $output);
+        }
+    };
+    (@bound $bound:path, $output:literal; dyn $trait:ident + Sync) => {
+        impl dyn $trait + Sync {
+            $crate::impl_downcast!(@methods $bound, $output);
+        }
+    };
+    (@methods $bound:path, $output:literal) => {
+        /// Returns some shared reference to the inner value if it is of type `T`, or
+        /// `None` if it isn't.
+        ///
+        /// Forwards to the method defined on the type `dyn Any`.
+        pub fn downcast_ref<T: $bound>(&self) -> ::core::option::Option<&T> {
+            (self as &dyn ::core::any::Any).downcast_ref::<T>()
+        }
+
+        /// Returns some exclusive reference to the inner value if it is of type `T`, or
+        /// `None` if it isn't.
+        ///
+        /// Forwards to the method defined on the type `dyn Any`.
+        pub fn downcast_mut<T: $bound>(&mut self) -> ::core::option::Option<&mut T> {
+            (self as &mut dyn ::core::any::Any).downcast_mut::<T>()
+        }
+
+        /// Returns `true` if the inner type is the same as `T`.
+        ///
+        /// Forwards to the method defined on the type `dyn Any`.
+        pub fn is<T: $bound>(&self) -> bool {
+            let this: &dyn ::core::any::Any = self;
+            this.is::<T>()
+        }
+
+        $crate::__if_type_name! {
+            /// Returns some shared reference to the inner value if it is of type `T`, or
+            /// an error naming the actual type if it isn't.
+            ///
+            /// ## Errors
+            ///
+            /// If the inner value is not of type `T`, returns a `TypeMismatch`.
+            pub fn try_downcast_ref<T: $bound>(
+                &self,
+            ) -> ::core::result::Result<&T, $crate::TypeMismatch> {
+                self.downcast_ref::<T>()
+                    .ok_or_else(|| $crate::__private::type_mismatch::<T, Self>(self))
+            }
+
+            /// Returns some exclusive reference to the inner value if it is of type `T`, or
+            /// an error naming the actual type if it isn't.
+            ///
+            /// ## Errors
+            ///
+            /// If the inner value is not of type `T`, returns a `TypeMismatch`.
+            pub fn try_downcast_mut<T: $bound>(
+                &mut self,
+            ) -> ::core::result::Result<&mut T, $crate::TypeMismatch> {
+                if self.is::<T>() {
+                    ::core::result::Result::Ok(self.downcast_mut::<T>().unwrap())
+                } else {
+                    ::core::result::Result::Err($crate::__private::type_mismatch::<T, Self>(self))
+                }
+            }
+        }
+
+        /// Returns a shared reference to the inner value, which must be of type `T`.
+        ///
+        /// ## Panics
+        ///
+        /// If the inner value is not of type `T`.
+        #[doc = concat!("The panic message includes the ", $output, " of the value.")]
+        #[track_caller]
+        pub fn expect_downcast_ref<T: $bound>(&self) -> &T {
+            match self.downcast_ref::<T>() {
+                ::core::option::Option::Some(value) => value,
+                ::core::option::Option::None => $crate::__private::unexpected_type::<T>(self),
+            }
+        }
+
+        /// Returns an exclusive reference to the inner value, which must be of type `T`.
+        ///
+        /// ## Panics
+        ///
+        /// If the inner value is not of type `T`.
+        #[doc = concat!("The panic message includes the ", $output, " of the value.")]
+        #[track_caller]
+        pub fn expect_downcast_mut<T: $bound>(&mut self) -> &mut T {
+            if !self.is::<T>() {
+                $crate::__private::unexpected_type::<T>(self)
+            }
+            self.downcast_mut::<T>().unwrap()
+        }
+
+        $crate::__if_alloc! {
+            /// Access the actual type of this value, which must be `T`.
+            ///
+            /// ## Panics
+            ///
+            /// If the inner value is not of type `T`.
+            #[doc = concat!("The panic message includes the ", $output, " of the value.")]
+            #[track_caller]
+            pub fn expect_downcast<T: $bound>(
+                self: $crate::__private::Box<Self>,
+            ) -> $crate::__private::Box<T> {
+                match self.downcast::<T>() {
+                    ::core::result::Result::Ok(value) => value,
+                    ::core::result::Result::Err(this) => {
+                        $crate::__private::unexpected_type::<T>(&*this)
+                    }
+                }
+            }
+        }
+
+        $crate::__if_alloc! {
+            /// Access the actual type of this value.
+            ///
+            /// Forwards to the method defined on the type `Box<dyn Any>`.
+            ///
+            /// ## Errors
+            ///
+            /// If the message contained within `self` is not of type `T`, returns `self`.
+            pub fn downcast<T: $bound>(
+                self: $crate::__private::Box<Self>,
+            ) -> ::core::result::Result<$crate::__private::Box<T>, $crate::__private::Box<Self>> {
+                if self.is::<T>() {
+                    ::core::result::Result::Ok(
+                        (self as $crate::__private::Box<dyn ::core::any::Any>)
+                            .downcast::<T>()
+                            .unwrap(),
+                    )
+                } else {
+                    ::core::result::Result::Err(self)
+                }
+            }
+
+            $crate::__if_type_name! {
+                /// Access the actual type of this value, describing the value if it is of
+                /// a different type.
+                ///
+                /// This is the same as `downcast`, except that the error describes the value
+                /// which was found, and can be propagated using `?`.
+                ///
+                /// ## Errors
+                ///
+                /// If the message contained within `self` is not of type `T`, returns a
+                #[doc = concat!("`DowncastError` with its type name and ", $output, ".")]
+                /// The value itself is dropped.
+                #[track_caller]
+                pub fn downcast_debug<T: $bound>(
+                    self: $crate::__private::Box<Self>,
+                ) -> ::core::result::Result<$crate::__private::Box<T>, $crate::DowncastError> {
+                    match self.downcast::<T>() {
+                        ::core::result::Result::Ok(value) => ::core::result::Result::Ok(value),
+                        ::core::result::Result::Err(this) => {
+                            ::core::result::Result::Err($crate::__private::downcast_error::<T, Self>(&*this))
+                        }
+                    }
+                }
+            }
+
+            /// Returns `true` if the inner value is itself a type-erased value, such as a
+            /// `Box<dyn AnyDebug>` or a `SharedAny`.
+            ///
+            /// This usually means that an already boxed value was boxed again, in which case
+            /// downcasting to the type of the original value will fail.
+            pub fn is_nested_box(&self) -> bool {
+                $crate::__private::is_erased_container(self)
+            }
+        }
+    };
+}
+
+/// Boxes a value as a `Box<dyn AnyDebug>`, or one of its combinations with `Send` and `Sync`.
+///
+/// This avoids writing out the type of the trait object, such as in
+/// `Box::new(value) as Box<dyn AnyDebug + Send>`.
+/// The auto traits of the trait object can be given before the value:
+///
+/// - `anydebug!(value)` creates a `Box<dyn AnyDebug>`.
+/// - `anydebug!(send: value)` creates a `Box<dyn AnyDebug + Send>`.
+/// - `anydebug!(sync: value)` creates a `Box<dyn AnyDebug + Sync>`.
+/// - `anydebug!(send + sync: value)` creates a `Box<dyn AnyDebug + Send + Sync>`.
+///
+/// This macro is only available if the `alloc` feature is enabled.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{anydebug, AnyDebug};
+///
+/// #[derive(Debug)]
+/// struct Click { x: f64 }
+///
+/// let messages: Vec<Box<dyn AnyDebug + Send>> = vec![
+///     anydebug!(send: Click { x: 1.5 }),
+///     anydebug!(send: "text"),
+/// ];
+/// assert!(messages[0].is::<Click>());
+///
+/// let message = anydebug!(7_u8);
+/// assert_eq!(format!("{message:?}"), "7");
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! anydebug {
+    (send + sync: $value:expr $(,)?) => {
+        $crate::anydebug!(@box $value, dyn $crate::AnyDebug + Send + Sync)
+    };
+    (sync + send: $value:expr $(,)?) => {
+        $crate::anydebug!(send + sync: $value)
+    };
+    (send: $value:expr $(,)?) => {
+        $crate::anydebug!(@box $value, dyn $crate::AnyDebug + Send)
+    };
+    (sync: $value:expr $(,)?) => {
+        $crate::anydebug!(@box $value, dyn $crate::AnyDebug + Sync)
+    };
+    (@box $value:expr, $ty:ty) => {{
+        let boxed: $crate::__private::Box<$ty> = $crate::__private::Box::new($value);
+        $crate::__private::warn_if_nested(&*boxed);
+        boxed
+    }};
+    ($value:expr $(,)?) => {
+        $crate::anydebug!(@box $value, dyn $crate::AnyDebug)
+    };
+}
+
+/// Runs the first arm whose type matches the type of a dynamically typed value.
+///
+/// Each arm binds a shared reference to the value if it is of the given type.
+/// A final fallback arm is required, which binds the value itself, dereferenced, for when
+/// none of the types match.
+/// This allows the fallback to report the unexpected value, for example using its `Debug`
+/// output or its [`type_name`](crate::AnyDebug::type_name).
+///
+/// The value can be any type which dereferences to a type with a `downcast_ref` method, such
+/// as `&dyn AnyDebug`, `Box<dyn AnyDebug + Send>`, or trait objects of traits which use
+/// [`impl_downcast`].
+/// It is borrowed rather than moved.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{match_downcast, AnyDebug};
+///
+/// #[derive(Debug)]
+/// struct Click { x: f64 }
+/// #[derive(Debug)]
+/// struct Scroll { delta: f64 }
+///
+/// fn describe(message: &dyn AnyDebug) -> String {
+///     match_downcast!(message,
+///         click: Click => format!("click at {}", click.x),
+///         scroll: Scroll => format!("scroll by {}", scroll.delta),
+///         other => format!("unhandled {other:?}"),
+///     )
+/// }
+///
+/// assert_eq!(describe(&Click { x: 1.5 }), "click at 1.5");
+/// assert_eq!(describe(&7_u8), "unhandled 7");
+/// ```
+///
+/// Leaving out the fallback arm is an error:
+///
+/// ```compile_fail
+/// # use anymore::{match_downcast, AnyDebug};
+/// # #[derive(Debug)]
+/// # struct Click { x: f64 }
+/// let message: Box<dyn AnyDebug> = Box::new(Click { x: 1.5 });
+/// match_downcast!(message,
+///     click: Click => println!("click at {}", click.x),
+/// );
+/// ```
+#[macro_export]
+macro_rules! match_downcast {
+    ($value:expr, $($arms:tt)+) => {
+        match &*$value {
+            value => $crate::match_downcast!(@arms value, $($arms)+),
+        }
+    };
+    (@arms $value:ident, $bind:ident: $ty:ty => $body:expr, $($rest:tt)+) => {
+        if let ::core::option::Option::Some($bind) = $value.downcast_ref::<$ty>() {
+            $body
+        } else {
+            $crate::match_downcast!(@arms $value, $($rest)+)
+        }
+    };
+    (@arms $value:ident, $bind:ident: $ty:ty => $body:expr $(,)?) => {
+        ::core::compile_error!(
+            "`match_downcast!` requires a fallback arm, such as `other => ...`, after the typed arms"
+        )
+    };
+    (@arms $value:ident, $other:ident => $fallback:expr $(,)?) => {{
+        let $other = $value;
+        $fallback
+    }};
+}
+
+/// Expands to its input if Anymore's `alloc` feature is enabled.
+///
+/// This is needed because `cfg` attributes in the output of [`impl_downcast`] would
+/// otherwise be evaluated against the features of the calling crate.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_alloc {
+    ($($tt:tt)*) => {
+        $($tt)*
+    };
+}
+
+/// Expands to its input if Anymore's `alloc` feature is enabled.
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_alloc {
+    ($($tt:tt)*) => {};
+}
+
+/// Expands to its input if Anymore's `type_name` feature is enabled.
+#[cfg(feature = "type_name")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_type_name {
+    ($($tt:tt)*) => {
+        $($tt)*
+    };
+}
+
+/// Expands to its input if Anymore's `type_name` feature is enabled.
+#[cfg(not(feature = "type_name"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_type_name {
+    ($($tt:tt)*) => {};
+}
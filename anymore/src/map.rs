@@ -0,0 +1,381 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Maps which store one value of each type.
+
+use alloc::boxed::Box;
+use alloc::collections::{btree_map, BTreeMap};
+use core::any::TypeId;
+use core::fmt::{self, Debug};
+use core::marker::PhantomData;
+
+use crate::AnyDebug;
+
+/// A map which stores at most one value of each type.
+///
+/// This is useful for storing values such as application context or widget properties, where
+/// each type is only needed once, and the type itself identifies the value.
+///
+/// Unlike a map of `dyn Any` values, the `Debug` output of this map contains the type name
+/// and the `Debug` output of every value.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::map::AnyMap;
+///
+/// #[derive(Debug)]
+/// struct Theme { dark: bool }
+///
+/// let mut map = AnyMap::new();
+/// map.insert(Theme { dark: true });
+/// map.insert(16_u32);
+/// assert!(map.get::<Theme>().unwrap().dark);
+/// *map.get_mut::<u32>().unwrap() += 1;
+/// assert_eq!(map.remove::<u32>(), Some(17));
+/// assert!(!map.contains::<u32>());
+/// ```
+#[derive(Default)]
+pub struct AnyMap {
+    map: BTreeMap<TypeId, Box<dyn AnyDebug>>,
+}
+
+impl AnyMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts `value`, returning the previous value of type `T`, if any.
+    pub fn insert<T: AnyDebug>(&mut self, value: T) -> Option<T> {
+        let previous = self.map.insert(TypeId::of::<T>(), Box::new(value))?;
+        Some(*previous.downcast::<T>().ok()?)
+    }
+
+    /// Returns a shared reference to the value of type `T`, if there is one.
+    pub fn get<T: AnyDebug>(&self) -> Option<&T> {
+        self.map.get(&TypeId::of::<T>())?.downcast_ref::<T>()
+    }
+
+    /// Returns an exclusive reference to the value of type `T`, if there is one.
+    pub fn get_mut<T: AnyDebug>(&mut self) -> Option<&mut T> {
+        self.map.get_mut(&TypeId::of::<T>())?.downcast_mut::<T>()
+    }
+
+    /// Removes and returns the value of type `T`, if there is one.
+    pub fn remove<T: AnyDebug>(&mut self) -> Option<T> {
+        let value = self.map.remove(&TypeId::of::<T>())?;
+        Some(*value.downcast::<T>().ok()?)
+    }
+
+    /// Returns `true` if the map contains a value of type `T`.
+    pub fn contains<T: AnyDebug>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Returns the entry for the value of type `T`, for in-place manipulation.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use anymore::map::AnyMap;
+    ///
+    /// #[derive(Debug, Default)]
+    /// struct ClickCount(u32);
+    ///
+    /// let mut map = AnyMap::new();
+    /// map.entry::<ClickCount>().or_default().0 += 1;
+    /// map.entry::<ClickCount>().or_default().0 += 1;
+    /// assert_eq!(map.get::<ClickCount>().unwrap().0, 2);
+    /// ```
+    pub fn entry<T: AnyDebug>(&mut self) -> Entry<'_, T> {
+        Entry {
+            entry: self.map.entry(TypeId::of::<T>()),
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl Debug for AnyMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.map.values().map(|value| (TypeLabel(&**value), value)))
+            .finish()
+    }
+}
+
+/// The entry for the value of type `T` in an [`AnyMap`], which may be vacant.
+///
+/// This is returned by [`AnyMap::entry`].
+pub struct Entry<'a, T> {
+    entry: btree_map::Entry<'a, TypeId, Box<dyn AnyDebug>>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: AnyDebug> Entry<'a, T> {
+    /// Returns the value, first inserting `default` if the entry is vacant.
+    pub fn or_insert(self, default: T) -> &'a mut T {
+        self.or_insert_with(|| default)
+    }
+
+    /// Returns the value, first inserting the result of `default` if the entry is vacant.
+    pub fn or_insert_with(self, default: impl FnOnce() -> T) -> &'a mut T {
+        let value = self.entry.or_insert_with(|| Box::new(default()));
+        (**value)
+            .downcast_mut::<T>()
+            .expect("entries are keyed by the `TypeId` of their value")
+    }
+
+    /// Returns the value, first inserting `T::default()` if the entry is vacant.
+    pub fn or_default(self) -> &'a mut T
+    where
+        T: Default,
+    {
+        self.or_insert_with(T::default)
+    }
+}
+
+impl<T> Debug for Entry<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Entry").field(&self.entry).finish()
+    }
+}
+
+/// A thread-safe map which stores at most one value of each type.
+///
+/// This is the same as [`AnyMap`], except that it can be shared between threads, and so
+/// only stores values which are `Send` and `Sync`.
+/// The map is protected by a [`RwLock`](std::sync::RwLock), so many threads can read from
+/// it at once.
+///
+/// As the values are behind a lock, they are accessed through the [`with`](Self::with)
+/// and [`with_mut`](Self::with_mut) methods rather than by reference.
+/// The lock is held while the closure passed to these methods runs, so the closure must
+/// not access the same map.
+///
+/// If a thread panics while holding the lock, the map remains usable.
+///
+/// ## Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use anymore::map::ConcurrentAnyMap;
+///
+/// #[derive(Debug)]
+/// struct FrameCount(u64);
+///
+/// let map = Arc::new(ConcurrentAnyMap::new());
+/// map.insert(FrameCount(0));
+/// let reader = {
+///     let map = Arc::clone(&map);
+///     std::thread::spawn(move || map.with(|count: &FrameCount| count.0))
+/// };
+/// map.with_mut(|count: &mut FrameCount| count.0 += 1);
+/// assert!(matches!(reader.join().unwrap(), Some(0 | 1)));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct ConcurrentAnyMap {
+    map: std::sync::RwLock<BTreeMap<TypeId, Box<dyn AnyDebug + Send + Sync>>>,
+}
+
+#[cfg(feature = "std")]
+impl ConcurrentAnyMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(
+        &self,
+    ) -> std::sync::RwLockReadGuard<'_, BTreeMap<TypeId, Box<dyn AnyDebug + Send + Sync>>> {
+        self.map
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn write(
+        &self,
+    ) -> std::sync::RwLockWriteGuard<'_, BTreeMap<TypeId, Box<dyn AnyDebug + Send + Sync>>> {
+        self.map
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Inserts `value`, returning the previous value of type `T`, if any.
+    pub fn insert<T: AnyDebug + Send + Sync>(&self, value: T) -> Option<T> {
+        let previous = self.write().insert(TypeId::of::<T>(), Box::new(value))?;
+        Some(*previous.downcast::<T>().ok()?)
+    }
+
+    /// Calls `f` with a shared reference to the value of type `T`, if there is one.
+    ///
+    /// Returns the result of `f`, or `None` if there is no value of type `T`.
+    pub fn with<T: AnyDebug + Send + Sync, R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let map = self.read();
+        map.get(&TypeId::of::<T>())?.downcast_ref::<T>().map(f)
+    }
+
+    /// Calls `f` with an exclusive reference to the value of type `T`, if there is one.
+    ///
+    /// Returns the result of `f`, or `None` if there is no value of type `T`.
+    pub fn with_mut<T: AnyDebug + Send + Sync, R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let mut map = self.write();
+        map.get_mut(&TypeId::of::<T>())?.downcast_mut::<T>().map(f)
+    }
+
+    /// Removes and returns the value of type `T`, if there is one.
+    pub fn remove<T: AnyDebug + Send + Sync>(&self) -> Option<T> {
+        let value = self.write().remove(&TypeId::of::<T>())?;
+        Some(*value.downcast::<T>().ok()?)
+    }
+
+    /// Returns `true` if the map contains a value of type `T`.
+    pub fn contains<T: AnyDebug + Send + Sync>(&self) -> bool {
+        self.read().contains_key(&TypeId::of::<T>())
+    }
+
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Debug for ConcurrentAnyMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let map = self.read();
+        f.debug_map()
+            .entries(map.values().map(|value| (TypeLabel(&**value), value)))
+            .finish()
+    }
+}
+
+/// Formats the type of a value, for use as the key in the `Debug` output of a map,
+/// or in error messages.
+///
+/// This is the type name if the `type_name` feature is enabled, or a
+/// [`ShortTypeId`](crate::ShortTypeId) otherwise.
+pub(crate) struct TypeLabel<'a>(pub(crate) &'a dyn AnyDebug);
+
+impl Debug for TypeLabel<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "type_name")]
+        let label = self.0.type_name();
+        #[cfg(not(feature = "type_name"))]
+        let label = crate::ShortTypeId::of_val(self.0);
+        write!(f, "{label}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnyMap;
+    use alloc::format;
+
+    #[derive(Debug, PartialEq)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn any_map_insert() {
+        let mut map = AnyMap::new();
+        assert_eq!(map.insert(SomeMessage(200)), None);
+        assert_eq!(map.insert(SomeMessage(201)), Some(SomeMessage(200)));
+        assert_eq!(map.insert(202_u32), None);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get::<SomeMessage>(), Some(&SomeMessage(201)));
+        assert_eq!(map.get::<u32>(), Some(&202));
+        assert!(map.get::<u8>().is_none());
+    }
+    #[test]
+    fn any_map_get_mut() {
+        let mut map = AnyMap::new();
+        map.insert(SomeMessage(203));
+        map.get_mut::<SomeMessage>().unwrap().0 = 204;
+        assert!(map.get_mut::<u8>().is_none());
+        assert_eq!(map.get::<SomeMessage>(), Some(&SomeMessage(204)));
+    }
+    #[test]
+    fn any_map_remove() {
+        let mut map = AnyMap::new();
+        map.insert(SomeMessage(205));
+        assert!(map.contains::<SomeMessage>());
+        assert_eq!(map.remove::<SomeMessage>(), Some(SomeMessage(205)));
+        assert_eq!(map.remove::<SomeMessage>(), None);
+        assert!(map.is_empty());
+    }
+    #[test]
+    fn any_map_debug() {
+        let mut map = AnyMap::new();
+        map.insert(SomeMessage(206));
+        let text = format!("{map:?}");
+        assert!(text.ends_with(": SomeMessage(206)}"), "{text}");
+        #[cfg(feature = "type_name")]
+        assert!(text.contains("SomeMessage:"), "{text}");
+    }
+
+    #[test]
+    fn any_map_entry() {
+        let mut map = AnyMap::new();
+        assert_eq!(map.entry().or_insert(SomeMessage(211)).0, 211);
+        assert_eq!(map.entry().or_insert(SomeMessage(212)).0, 211);
+        map.entry::<SomeMessage>()
+            .or_insert_with(|| unreachable!())
+            .0 += 2;
+        assert_eq!(map.get::<SomeMessage>(), Some(&SomeMessage(213)));
+        *map.entry::<u32>().or_default() += 214;
+        assert_eq!(map.get::<u32>(), Some(&214));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn concurrent_any_map_with() {
+        use super::ConcurrentAnyMap;
+
+        let map = ConcurrentAnyMap::new();
+        assert_eq!(map.insert(SomeMessage(207)), None);
+        assert_eq!(map.with(|val: &SomeMessage| val.0), Some(207));
+        assert_eq!(map.with_mut(|val: &mut SomeMessage| val.0 += 1), Some(()));
+        assert_eq!(map.with(|val: &u32| *val), None);
+        assert_eq!(map.insert(SomeMessage(209)), Some(SomeMessage(208)));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.remove::<SomeMessage>(), Some(SomeMessage(209)));
+        assert!(map.is_empty());
+    }
+    #[test]
+    #[cfg(feature = "std")]
+    fn concurrent_any_map_threads() {
+        extern crate std;
+        use super::ConcurrentAnyMap;
+        use alloc::sync::Arc;
+
+        let map = Arc::new(ConcurrentAnyMap::new());
+        map.insert(SomeMessage(210));
+        let handles: alloc::vec::Vec<_> = (0..4)
+            .map(|_| {
+                let map = Arc::clone(&map);
+                std::thread::spawn(move || map.with_mut(|val: &mut SomeMessage| val.0 += 1))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(map.with(|val: &SomeMessage| val.0), Some(214));
+        assert!(format!("{map:?}").ends_with(": SomeMessage(214)}"));
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Dynamically typed messages with metadata.
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use core::fmt::{self, Debug};
+
+use crate::map::TypeLabel;
+use crate::{AnyDebug, Payload};
+
+/// A dynamically typed message, with optional metadata about where and when it was sent.
+///
+/// The metadata is the id of the sender, a sequence number, and (if the `std` feature is
+/// enabled) the time at which the message was sent.
+/// The meaning of the sender id and sequence number is up to the application.
+///
+/// The `Debug` output shows the metadata which is present, followed by the payload.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::message::Message;
+///
+/// #[derive(Debug)]
+/// struct Click { x: f64 }
+///
+/// let message = Message::new(Click { x: 1.5 }).with_sender(4).with_sequence(12);
+/// assert_eq!(
+///     format!("{message:?}"),
+///     "Message { sender: 4, sequence: 12, payload: Click { x: 1.5 } }"
+/// );
+/// let click: Box<Click> = message.downcast().unwrap();
+/// ```
+pub struct Message {
+    payload: Box<dyn AnyDebug + Send>,
+    sender: Option<u64>,
+    sequence: Option<u64>,
+    #[cfg(feature = "std")]
+    timestamp: Option<std::time::Instant>,
+}
+
+impl Message {
+    /// Creates a message containing `payload`, without any metadata.
+    ///
+    /// To create a message from an already boxed value, use [`from_boxed`](Self::from_boxed).
+    #[track_caller]
+    pub fn new<T: AnyDebug + Send>(payload: T) -> Self {
+        Self::from_boxed(Box::new(payload))
+    }
+
+    /// Creates a message containing the already boxed `payload`, without any metadata.
+    #[track_caller]
+    pub fn from_boxed(payload: Box<dyn AnyDebug + Send>) -> Self {
+        crate::__private::warn_if_nested(&*payload);
+        Self {
+            payload,
+            sender: None,
+            sequence: None,
+            #[cfg(feature = "std")]
+            timestamp: None,
+        }
+    }
+
+    /// Returns the message with the id of its sender set to `sender`.
+    pub fn with_sender(mut self, sender: u64) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Returns the message with its sequence number set to `sequence`.
+    pub fn with_sequence(mut self, sequence: u64) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+
+    /// Returns the message with the time at which it was sent set to `timestamp`.
+    ///
+    /// This method is only available if the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    pub fn with_timestamp(mut self, timestamp: std::time::Instant) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Returns the message with the time at which it was sent set to the current time.
+    ///
+    /// This method is only available if the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    pub fn timestamped(self) -> Self {
+        self.with_timestamp(std::time::Instant::now())
+    }
+
+    /// The id of the sender of the message, if it was set.
+    pub fn sender(&self) -> Option<u64> {
+        self.sender
+    }
+
+    /// The sequence number of the message, if it was set.
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    /// The time at which the message was sent, if it was set.
+    ///
+    /// This method is only available if the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    pub fn timestamp(&self) -> Option<std::time::Instant> {
+        self.timestamp
+    }
+
+    /// The payload of the message.
+    pub fn payload(&self) -> &(dyn AnyDebug + Send) {
+        &*self.payload
+    }
+
+    /// The payload of the message, mutably.
+    pub fn payload_mut(&mut self) -> &mut (dyn AnyDebug + Send) {
+        &mut *self.payload
+    }
+
+    /// Returns the payload of the message, discarding the metadata.
+    pub fn into_payload(self) -> Box<dyn AnyDebug + Send> {
+        self.payload
+    }
+
+    /// Returns `true` if the payload is of type `T`.
+    pub fn is<T: AnyDebug>(&self) -> bool {
+        self.payload.is::<T>()
+    }
+
+    /// Returns a shared reference to the payload if it is of type `T`, or `None` if it isn't.
+    pub fn downcast_ref<T: AnyDebug>(&self) -> Option<&T> {
+        self.payload.downcast_ref()
+    }
+
+    /// Returns an exclusive reference to the payload if it is of type `T`, or `None` if it isn't.
+    pub fn downcast_mut<T: AnyDebug>(&mut self) -> Option<&mut T> {
+        self.payload.downcast_mut()
+    }
+
+    /// Returns the payload if it is of type `T`, discarding the metadata.
+    ///
+    /// ## Errors
+    ///
+    /// If the payload is not of type `T`, returns `self`, including the metadata.
+    pub fn downcast<T: AnyDebug>(self) -> Result<Box<T>, Self> {
+        if self.is::<T>() {
+            Ok(self.payload.downcast().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Payload for Message {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        &*self.payload
+    }
+}
+
+impl Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Message");
+        if let Some(sender) = &self.sender {
+            debug.field("sender", sender);
+        }
+        if let Some(sequence) = &self.sequence {
+            debug.field("sequence", sequence);
+        }
+        #[cfg(feature = "std")]
+        if let Some(timestamp) = &self.timestamp {
+            debug.field("timestamp", timestamp);
+        }
+        debug.field("payload", &self.payload).finish()
+    }
+}
+
+/// A first-in, first-out queue of dynamically typed messages.
+///
+/// The `Debug` output lists the pending messages, from the front of the queue, each
+/// labeled with its type name (or a [`ShortTypeId`](crate::ShortTypeId) if the `type_name`
+/// feature is disabled).
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::message::MessageQueue;
+///
+/// #[derive(Debug)]
+/// struct Click { x: f64 }
+///
+/// let mut queue = MessageQueue::new();
+/// queue.push(Click { x: 1.5 });
+/// queue.push(7_u8);
+/// assert!(queue.pop_downcast::<u8>().is_none());
+/// assert_eq!(queue.pop_downcast::<Click>().unwrap().x, 1.5);
+/// assert_eq!(queue.len(), 1);
+/// # #[cfg(feature = "type_name")]
+/// assert_eq!(format!("{queue:?}"), "[u8: 7]");
+/// ```
+#[derive(Default)]
+pub struct MessageQueue {
+    messages: VecDeque<Box<dyn AnyDebug + Send>>,
+}
+
+impl MessageQueue {
+    /// Creates an empty queue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `message` to the back of the queue.
+    ///
+    /// To add an already boxed message, use [`push_boxed`](Self::push_boxed).
+    #[track_caller]
+    pub fn push<T: AnyDebug + Send>(&mut self, message: T) {
+        self.push_boxed(Box::new(message));
+    }
+
+    /// Adds the already boxed `message` to the back of the queue.
+    #[track_caller]
+    pub fn push_boxed(&mut self, message: Box<dyn AnyDebug + Send>) {
+        crate::__private::warn_if_nested(&*message);
+        self.messages.push_back(message);
+    }
+
+    /// Removes the message at the front of the queue and returns it, or `None` if the
+    /// queue is empty.
+    pub fn pop(&mut self) -> Option<Box<dyn AnyDebug + Send>> {
+        self.messages.pop_front()
+    }
+
+    /// Removes the message at the front of the queue and returns it, if it is of type `T`.
+    ///
+    /// If the queue is empty, or the message at the front is of a different type, the queue
+    /// is left unchanged and `None` is returned.
+    pub fn pop_downcast<T: AnyDebug>(&mut self) -> Option<Box<T>> {
+        if self.front()?.is::<T>() {
+            self.messages.pop_front()?.downcast().ok()
+        } else {
+            None
+        }
+    }
+
+    /// The message at the front of the queue, or `None` if the queue is empty.
+    pub fn front(&self) -> Option<&(dyn AnyDebug + Send)> {
+        self.messages.front().map(|message| &**message)
+    }
+
+    /// Returns the number of pending messages.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns `true` if there are no pending messages.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl Debug for MessageQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.messages.iter().map(|message| Pending(&**message)))
+            .finish()
+    }
+}
+
+/// Formats a pending message in a [`MessageQueue`], labeled with its type.
+struct Pending<'a>(&'a dyn AnyDebug);
+
+impl Debug for Pending<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: ", TypeLabel(self.0))?;
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Message, MessageQueue};
+    use alloc::boxed::Box;
+    use alloc::format;
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn message_downcast() {
+        let mut message = Message::new(SomeMessage(280)).with_sequence(1);
+        assert!(message.is::<SomeMessage>());
+        message.downcast_mut::<SomeMessage>().unwrap().0 = 281;
+        let message = message.downcast::<u32>().unwrap_err();
+        assert_eq!(message.sequence(), Some(1));
+        assert_eq!(message.downcast::<SomeMessage>().unwrap().0, 281);
+    }
+    #[test]
+    fn message_from_boxed() {
+        let message = Message::from_boxed(Box::new(SomeMessage(282)));
+        assert!(!message.payload().is_nested_box());
+        assert_eq!(message.downcast_ref::<SomeMessage>().unwrap().0, 282);
+        assert!(message.into_payload().is::<SomeMessage>());
+    }
+    #[test]
+    fn message_debug() {
+        let message = Message::new(SomeMessage(283));
+        assert_eq!(
+            format!("{message:?}"),
+            "Message { payload: SomeMessage(283) }"
+        );
+        let message = message.with_sender(284);
+        assert_eq!(message.sender(), Some(284));
+        assert_eq!(
+            format!("{message:?}"),
+            "Message { sender: 284, payload: SomeMessage(283) }"
+        );
+    }
+    #[test]
+    #[cfg(feature = "std")]
+    fn message_timestamp() {
+        let message = Message::new(SomeMessage(285));
+        assert!(message.timestamp().is_none());
+        let message = message.timestamped();
+        assert!(message.timestamp().is_some());
+        assert!(format!("{message:?}").contains("timestamp: "));
+    }
+    #[test]
+    fn message_queue_pop_downcast() {
+        let mut queue = MessageQueue::new();
+        assert!(queue.pop_downcast::<u32>().is_none());
+        queue.push(SomeMessage(300));
+        queue.push_boxed(Box::new(301_u32));
+        assert!(queue.pop_downcast::<u32>().is_none());
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop_downcast::<SomeMessage>().unwrap().0, 300);
+        assert_eq!(queue.front().unwrap().downcast_ref::<u32>(), Some(&301));
+        assert!(queue.pop().unwrap().is::<u32>());
+        assert!(queue.is_empty());
+    }
+    #[test]
+    #[cfg(feature = "type_name")]
+    fn message_queue_debug() {
+        let mut queue = MessageQueue::new();
+        queue.push(302_u16);
+        queue.push(SomeMessage(303));
+        assert_eq!(
+            format!("{queue:?}"),
+            "[u16: 302, anymore::message::tests::SomeMessage: SomeMessage(303)]"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A wrapper which allows types without a `Debug` implementation to be used as `AnyDebug`.
+
+use core::any::Any;
+use core::fmt::{self, Debug};
+use core::ops::{Deref, DerefMut};
+
+use crate::AnyDebug;
+
+/// A wrapper which implements [`Debug`] for any type, so that it can be used as an
+/// [`AnyDebug`] value.
+///
+/// This is useful for sending values of third-party types which don't implement `Debug`.
+/// The `Debug` output names the type of the wrapped value, and includes the label given
+/// to [`with_label`](Opaque::with_label), if any.
+///
+/// The wrapped value can be accessed through [`Deref`], or with [`into_inner`](Opaque::into_inner).
+/// [`Opaque::downcast_ref`] and the related functions access a `dyn AnyDebug` value
+/// which is either an `Opaque<T>` or a `T`.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{AnyDebug, Opaque};
+///
+/// struct Texture { id: u32 }
+///
+/// let message = Opaque::with_label(Texture { id: 7 }, "background");
+/// let message: &dyn AnyDebug = &message;
+/// assert!(format!("{message:?}").ends_with("Texture>(\"background\")"));
+///
+/// let texture = Opaque::<Texture>::downcast_ref(message).unwrap();
+/// assert_eq!(texture.id, 7);
+/// ```
+#[derive(Clone)]
+pub struct Opaque<T> {
+    value: T,
+    label: Option<&'static str>,
+}
+
+impl<T: Any> Opaque<T> {
+    /// Wraps `value`.
+    pub fn new(value: T) -> Self {
+        Self { value, label: None }
+    }
+
+    /// Wraps `value`, with a label which will be included in the `Debug` output.
+    pub fn with_label(value: T, label: &'static str) -> Self {
+        Self {
+            value,
+            label: Some(label),
+        }
+    }
+
+    /// Returns the label given to [`with_label`](Opaque::with_label), if any.
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Returns some shared reference to the `T` in `value`, if it is either an `Opaque<T>`
+    /// or a `T`, or `None` otherwise.
+    pub fn downcast_ref(value: &dyn AnyDebug) -> Option<&T> {
+        let value: &dyn Any = value;
+        match value.downcast_ref::<Self>() {
+            Some(opaque) => Some(&opaque.value),
+            None => value.downcast_ref::<T>(),
+        }
+    }
+
+    /// Returns some exclusive reference to the `T` in `value`, if it is either an `Opaque<T>`
+    /// or a `T`, or `None` otherwise.
+    pub fn downcast_mut(value: &mut dyn AnyDebug) -> Option<&mut T> {
+        let value: &mut dyn Any = value;
+        if value.is::<Self>() {
+            value.downcast_mut::<Self>().map(|opaque| &mut opaque.value)
+        } else {
+            value.downcast_mut::<T>()
+        }
+    }
+
+    /// Access the `T` in `value`, if it is either an `Opaque<T>` or a `T`.
+    ///
+    /// `value` can be a `Box<dyn AnyDebug>`, or one of its combinations with `Send` and `Sync`.
+    ///
+    /// ## Errors
+    ///
+    /// If the value contained within `value` is neither an `Opaque<T>` nor a `T`,
+    /// returns `value`.
+    #[cfg(feature = "alloc")]
+    pub fn downcast<B: ?Sized + crate::__private::ErasedAnyDebug>(
+        value: alloc::boxed::Box<B>,
+    ) -> Result<alloc::boxed::Box<T>, alloc::boxed::Box<B>> {
+        let type_id = (*value).type_id();
+        if type_id == core::any::TypeId::of::<Self>() {
+            let opaque = value.into_any().downcast::<Self>().unwrap();
+            Ok(alloc::boxed::Box::new(opaque.value))
+        } else if type_id == core::any::TypeId::of::<T>() {
+            Ok(value.into_any().downcast::<T>().unwrap())
+        } else {
+            Err(value)
+        }
+    }
+}
+
+impl<T: Any> Debug for Opaque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Opaque<{}>", core::any::type_name::<T>())?;
+        if let Some(label) = self.label {
+            write!(f, "({label:?})")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Any> From<T> for Opaque<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Deref for Opaque<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Opaque<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::Opaque;
+    use crate::AnyDebug;
+    use alloc::format;
+
+    struct NotDebug(u32);
+
+    #[test]
+    fn opaque_debug() {
+        let val = Opaque::new(NotDebug(160));
+        assert!(format!("{val:?}").starts_with("Opaque<"));
+        assert!(format!("{val:?}").ends_with("NotDebug>"));
+        let val = Opaque::with_label(NotDebug(161), "label");
+        assert!(format!("{val:?}").ends_with("NotDebug>(\"label\")"));
+        assert_eq!(val.label(), Some("label"));
+    }
+    #[test]
+    fn opaque_deref() {
+        let mut val = Opaque::from(NotDebug(162));
+        val.0 = 163;
+        assert_eq!(val.into_inner().0, 163);
+    }
+    #[test]
+    fn opaque_downcast_ref() {
+        let val = Opaque::new(NotDebug(164));
+        let val: &(dyn AnyDebug + Send) = &val;
+        assert_eq!(Opaque::<NotDebug>::downcast_ref(val).unwrap().0, 164);
+        assert!(Opaque::<u32>::downcast_ref(val).is_none());
+        assert_eq!(Opaque::<u32>::downcast_ref(&165_u32), Some(&165));
+    }
+    #[test]
+    fn opaque_downcast_mut() {
+        let mut val = Opaque::new(NotDebug(166));
+        Opaque::<NotDebug>::downcast_mut(&mut val).unwrap().0 = 167;
+        assert_eq!(val.0, 167);
+        let mut number = 168_u32;
+        *Opaque::<u32>::downcast_mut(&mut number).unwrap() += 1;
+        assert_eq!(number, 169);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn opaque_downcast() {
+        use alloc::boxed::Box;
+
+        let val: Box<dyn AnyDebug> = Box::new(Opaque::new(NotDebug(170)));
+        let val = Opaque::<u32>::downcast(val).unwrap_err();
+        assert_eq!(Opaque::<NotDebug>::downcast(val).unwrap().0, 170);
+        let val: Box<dyn AnyDebug> = Box::new(171_u32);
+        assert_eq!(*Opaque::<u32>::downcast(val).unwrap(), 171);
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn opaque_downcast_send_sync() {
+        use alloc::boxed::Box;
+
+        let val: Box<dyn AnyDebug + Send> = Box::new(Opaque::new(172_u16));
+        let val = Opaque::<u32>::downcast(val).unwrap_err();
+        assert_eq!(*Opaque::<u16>::downcast(val).unwrap(), 172);
+        let val: Box<dyn AnyDebug + Send + Sync> = Box::new(173_u32);
+        assert_eq!(*Opaque::<u32>::downcast(val).unwrap(), 173);
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Inspecting the payloads of panics.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::any::Any;
+use core::fmt::{self, Debug, Display};
+use std::panic::UnwindSafe;
+
+use crate::{AnyDebug, Promoter, ShortTypeId};
+
+/// The payload of a caught panic, with its `Debug` output if it can be recovered.
+///
+/// The payloads of panics are `Box<dyn Any + Send>`, so can't be printed directly.
+/// This recognizes the payloads of panics with a message, which are a `&'static str` or a
+/// `String`, and payloads passed to [`panic_any`](std::panic::panic_any) as a
+/// `Box<dyn AnyDebug + Send>`.
+/// The payloads of other types can be recognized using a [`Promoter`], with
+/// [`with_promoter`](Self::with_promoter).
+/// For payloads which aren't recognized, only the [`ShortTypeId`] of their type is available,
+/// as `dyn Any` doesn't provide the name of its type.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{anydebug, catch_panic};
+///
+/// #[derive(Debug)]
+/// struct InvalidState { step: u32 }
+///
+/// let payload = catch_panic(|| std::panic::panic_any(anydebug!(send: InvalidState { step: 3 })))
+///     .unwrap_err();
+/// assert_eq!(format!("{payload:?}"), "PanicPayload(InvalidState { step: 3 })");
+///
+/// let payload = catch_panic(|| panic!("step {} failed", 4)).unwrap_err();
+/// assert_eq!(payload.message(), Some("step 4 failed"));
+/// ```
+pub struct PanicPayload {
+    inner: Inner,
+}
+
+enum Inner {
+    Recognized(Box<dyn AnyDebug + Send>),
+    Unknown(Box<dyn Any + Send>),
+}
+
+impl PanicPayload {
+    /// Wraps the payload of a panic, such as one returned by
+    /// [`std::panic::catch_unwind`].
+    pub fn new(payload: Box<dyn Any + Send>) -> Self {
+        Self::with_promoter(payload, &Promoter::new())
+    }
+
+    /// Wraps the payload of a panic, also recognizing the types registered in `promoter`
+    /// using [`Promoter::register_send`].
+    pub fn with_promoter(payload: Box<dyn Any + Send>, promoter: &Promoter) -> Self {
+        let payload = match payload.downcast::<Box<dyn AnyDebug + Send>>() {
+            Ok(payload) => return Self::recognized(*payload),
+            Err(payload) => payload,
+        };
+        let payload = match payload.downcast::<&'static str>() {
+            Ok(payload) => return Self::recognized(payload),
+            Err(payload) => payload,
+        };
+        let payload = match payload.downcast::<String>() {
+            Ok(payload) => return Self::recognized(payload),
+            Err(payload) => payload,
+        };
+        let inner = match promoter.promote_send(payload) {
+            Ok(payload) => Inner::Recognized(payload),
+            Err(payload) => Inner::Unknown(payload),
+        };
+        Self { inner }
+    }
+
+    fn recognized(payload: Box<dyn AnyDebug + Send>) -> Self {
+        Self {
+            inner: Inner::Recognized(payload),
+        }
+    }
+
+    /// The message of the panic, if it was a panic with a message such as from `panic!`.
+    pub fn message(&self) -> Option<&str> {
+        let payload = self.as_any_debug()?;
+        if let Some(message) = payload.downcast_ref::<&'static str>() {
+            return Some(message);
+        }
+        payload.downcast_ref::<String>().map(String::as_str)
+    }
+
+    /// The payload as a `dyn AnyDebug`, if its type was recognized.
+    pub fn as_any_debug(&self) -> Option<&(dyn AnyDebug + Send)> {
+        match &self.inner {
+            Inner::Recognized(payload) => Some(&**payload),
+            Inner::Unknown(_) => None,
+        }
+    }
+
+    /// The [`ShortTypeId`] of the type of the payload.
+    ///
+    /// This is available even if the type of the payload wasn't recognized.
+    pub fn short_type_id(&self) -> ShortTypeId {
+        match &self.inner {
+            Inner::Recognized(payload) => ShortTypeId::of_val(&**payload),
+            Inner::Unknown(payload) => ShortTypeId::from((**payload).type_id()),
+        }
+    }
+
+    /// Returns the original payload, such as to continue the panic using
+    /// [`std::panic::resume_unwind`].
+    ///
+    /// The payloads of panics with a message, and of recognized types, are returned as
+    /// their original type, except that payloads which were passed to `panic_any` as a
+    /// `Box<dyn AnyDebug + Send>` are returned as that value's type.
+    pub fn into_inner(self) -> Box<dyn Any + Send> {
+        match self.inner {
+            Inner::Recognized(payload) => payload,
+            Inner::Unknown(payload) => payload,
+        }
+    }
+}
+
+impl From<Box<dyn Any + Send>> for PanicPayload {
+    fn from(payload: Box<dyn Any + Send>) -> Self {
+        Self::new(payload)
+    }
+}
+
+impl Debug for PanicPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.inner {
+            Inner::Recognized(payload) => f.debug_tuple("PanicPayload").field(payload).finish(),
+            Inner::Unknown(_) => f
+                .debug_tuple("PanicPayload")
+                .field(&format_args!("<unknown type {}>", self.short_type_id()))
+                .finish(),
+        }
+    }
+}
+
+impl Display for PanicPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(message) = self.message() {
+            return f.write_str(message);
+        }
+        match &self.inner {
+            Inner::Recognized(payload) => write!(f, "panicked with {payload:?}"),
+            Inner::Unknown(_) => write!(
+                f,
+                "panicked with a value of unknown type {}",
+                self.short_type_id()
+            ),
+        }
+    }
+}
+
+/// Runs `f`, catching a panic and wrapping its payload in a [`PanicPayload`].
+///
+/// This is the same as [`std::panic::catch_unwind`], except for the type of the error.
+///
+/// ## Errors
+///
+/// If `f` panics, returns the payload of the panic.
+pub fn catch_panic<R>(f: impl FnOnce() -> R + UnwindSafe) -> Result<R, PanicPayload> {
+    std::panic::catch_unwind(f).map_err(PanicPayload::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{catch_panic, PanicPayload};
+    use crate::{Promoter, ShortTypeId};
+    use alloc::boxed::Box;
+    use alloc::format;
+    use alloc::string::ToString;
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn panic_payload_message() {
+        let payload = catch_panic(|| panic!("literal")).unwrap_err();
+        assert_eq!(payload.message(), Some("literal"));
+        assert_eq!(format!("{payload:?}"), r#"PanicPayload("literal")"#);
+        let payload = catch_panic(|| panic!("formatted {}", 250)).unwrap_err();
+        assert_eq!(payload.to_string(), "formatted 250");
+        assert_eq!(catch_panic(|| 251).unwrap(), 251);
+    }
+    #[test]
+    fn panic_payload_any_debug() {
+        let payload =
+            catch_panic(|| std::panic::panic_any(crate::anydebug!(send: SomeMessage(252))))
+                .unwrap_err();
+        assert!(payload.message().is_none());
+        let val = payload.as_any_debug().unwrap();
+        assert_eq!(val.downcast_ref::<SomeMessage>().unwrap().0, 252);
+        assert_eq!(payload.to_string(), "panicked with SomeMessage(252)");
+        assert_eq!(payload.short_type_id(), ShortTypeId::of::<SomeMessage>());
+        assert!(payload.into_inner().is::<SomeMessage>());
+    }
+    #[test]
+    fn panic_payload_promoter() {
+        let mut promoter = Promoter::new();
+        promoter.register_send::<SomeMessage>();
+        let payload = PanicPayload::with_promoter(Box::new(SomeMessage(253)), &promoter);
+        assert_eq!(format!("{payload:?}"), "PanicPayload(SomeMessage(253))");
+        let payload = PanicPayload::new(Box::new(SomeMessage(254)));
+        assert!(payload.as_any_debug().is_none());
+        let id = ShortTypeId::of::<SomeMessage>();
+        assert_eq!(
+            format!("{payload:?}"),
+            format!("PanicPayload(<unknown type {id}>)")
+        );
+        assert_eq!(payload.short_type_id(), id);
+        assert!(payload.into_inner().is::<SomeMessage>());
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Accepting `dyn AnyDebug` values however they are held.
+
+use crate::AnyDebug;
+
+/// A type which holds a `dyn AnyDebug` value, either directly or behind a pointer.
+///
+/// This allows a function to accept a dynamically typed value without choosing between
+/// `&dyn AnyDebug`, `Box<dyn AnyDebug>`, or another way of holding it.
+/// It is implemented for `dyn AnyDebug` (and its combinations with `Send` and `Sync`),
+/// for references, `Box`es, `Rc`s and `Arc`s of any `Payload`, and for [`SharedAny`](crate::SharedAny)
+/// and [`LocalShared`](crate::LocalShared).
+///
+/// The methods of this trait always inspect the innermost value, so `Box<dyn AnyDebug>` and
+/// `&Box<dyn AnyDebug>` both give access to the boxed value, rather than to the box.
+/// Note that `AnyDebug` is also implemented for `Box<dyn AnyDebug>`, so a box which has
+/// itself been boxed as a `dyn AnyDebug` will be inspected as the inner box.
+///
+/// `Payload` can be implemented for trait objects of downstream traits which have `AnyDebug`
+/// as a supertrait.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{AnyDebug, Payload};
+///
+/// #[derive(Debug)]
+/// struct Click;
+///
+/// fn is_click(message: impl Payload) -> bool {
+///     message.is::<Click>()
+/// }
+///
+/// let click: &dyn AnyDebug = &Click;
+/// assert!(is_click(click));
+/// let click: &(dyn AnyDebug + Send) = &Click;
+/// assert!(is_click(click));
+/// assert!(!is_click(&7_u32 as &dyn AnyDebug));
+/// ```
+pub trait Payload {
+    /// Returns the contained value.
+    fn as_any_debug(&self) -> &dyn AnyDebug;
+
+    /// Returns `true` if the contained value is of type `T`.
+    fn is<T: AnyDebug>(&self) -> bool {
+        self.as_any_debug().is::<T>()
+    }
+
+    /// Returns some shared reference to the contained value if it is of type `T`, or
+    /// `None` if it isn't.
+    fn downcast_ref<T: AnyDebug>(&self) -> Option<&T> {
+        self.as_any_debug().downcast_ref::<T>()
+    }
+}
+
+impl Payload for dyn AnyDebug {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        self
+    }
+}
+
+impl Payload for dyn AnyDebug + Send {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        self
+    }
+}
+
+impl Payload for dyn AnyDebug + Sync {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        self
+    }
+}
+
+impl Payload for dyn AnyDebug + Send + Sync {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        self
+    }
+}
+
+impl<P: Payload + ?Sized> Payload for &P {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        (**self).as_any_debug()
+    }
+}
+
+impl<P: Payload + ?Sized> Payload for &mut P {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        (**self).as_any_debug()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Payload + ?Sized> Payload for alloc::boxed::Box<P> {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        (**self).as_any_debug()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Payload + ?Sized> Payload for alloc::rc::Rc<P> {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        (**self).as_any_debug()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Payload + ?Sized> Payload for alloc::sync::Arc<P> {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        (**self).as_any_debug()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Payload for crate::SharedAny {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        &**self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Payload for crate::LocalShared {
+    fn as_any_debug(&self) -> &dyn AnyDebug {
+        &**self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Payload;
+    use crate::AnyDebug;
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    fn message_number(payload: impl Payload) -> Option<u32> {
+        payload
+            .downcast_ref::<SomeMessage>()
+            .map(|message| message.0)
+    }
+
+    #[test]
+    fn payload_reference() {
+        let val: &(dyn AnyDebug + Send) = &SomeMessage(130);
+        assert_eq!(message_number(val), Some(130));
+        assert!(!val.as_any_debug().is::<u32>());
+    }
+    #[test]
+    fn payload_mut_reference() {
+        let mut val = SomeMessage(131);
+        let val: &mut dyn AnyDebug = &mut val;
+        assert_eq!(message_number(val), Some(131));
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn payload_box() {
+        use alloc::boxed::Box;
+
+        let val: Box<dyn AnyDebug + Send + Sync> = Box::new(SomeMessage(132));
+        assert_eq!(message_number(&val), Some(132));
+        assert_eq!(message_number(val), Some(132));
+
+        let nested: Box<dyn AnyDebug> = Box::new(Box::new(SomeMessage(133)) as Box<dyn AnyDebug>);
+        assert_eq!(message_number(&nested), None);
+        assert!(nested.is::<Box<dyn AnyDebug>>());
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn payload_shared() {
+        use crate::{LocalShared, SharedAny};
+        use alloc::rc::Rc;
+        use alloc::sync::Arc;
+
+        let arc: Arc<dyn AnyDebug + Send + Sync> = Arc::new(SomeMessage(134));
+        assert_eq!(message_number(arc.clone()), Some(134));
+        assert_eq!(message_number(SharedAny::from(arc)), Some(134));
+        let rc: Rc<dyn AnyDebug> = Rc::new(SomeMessage(135));
+        assert_eq!(message_number(&rc), Some(135));
+        assert_eq!(message_number(LocalShared::from(rc)), Some(135));
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Recovering `dyn AnyDebug` values from plain `dyn Any` values.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use core::any::{Any, TypeId};
+use core::fmt::{self, Debug};
+
+use crate::AnyDebug;
+
+/// A set of registered types, which allows `dyn Any` values of those types to be
+/// converted into `dyn AnyDebug` values.
+///
+/// Values which are passed through APIs which only use `dyn Any`, such as standard library
+/// channels of `Box<dyn Any + Send>` or the payloads of panics, lose their `Debug`
+/// implementation.
+/// If the concrete type of such a value has been registered, [`promote`](Promoter::promote)
+/// recovers it.
+///
+/// ## Examples
+///
+/// ```
+/// use std::any::Any;
+/// use anymore::Promoter;
+///
+/// #[derive(Debug)]
+/// struct Click { x: f64 }
+///
+/// let mut promoter = Promoter::new();
+/// promoter.register::<Click>();
+///
+/// let message: Box<dyn Any> = Box::new(Click { x: 1.5 });
+/// let message = promoter.promote(message).unwrap();
+/// assert_eq!(format!("{message:?}"), "Click { x: 1.5 }");
+///
+/// let unknown: Box<dyn Any> = Box::new(7_u8);
+/// assert!(promoter.promote(unknown).is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct Promoter {
+    types: BTreeMap<TypeId, Registration>,
+}
+
+/// Converts a boxed value of a registered type.
+type BoxedConversion = fn(Box<dyn Any>) -> Box<dyn AnyDebug>;
+
+/// Converts a boxed value of a type registered with [`Promoter::register_send`].
+type BoxedSendConversion = fn(Box<dyn Any + Send>) -> Box<dyn AnyDebug + Send>;
+
+/// The conversions for a single registered type.
+#[derive(Clone, Copy)]
+struct Registration {
+    #[cfg(feature = "type_name")]
+    type_name: &'static str,
+    by_ref: fn(&dyn Any) -> Option<&dyn AnyDebug>,
+    boxed: BoxedConversion,
+    boxed_send: Option<BoxedSendConversion>,
+}
+
+impl Registration {
+    fn new<T: AnyDebug>() -> Self {
+        Self {
+            #[cfg(feature = "type_name")]
+            type_name: core::any::type_name::<T>(),
+            by_ref: |value| {
+                value
+                    .downcast_ref::<T>()
+                    .map(|value| value as &dyn AnyDebug)
+            },
+            boxed: |value| value.downcast::<T>().unwrap(),
+            boxed_send: None,
+        }
+    }
+}
+
+impl Promoter {
+    /// Creates a promoter with no registered types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the type `T`, so that values of this type can be promoted.
+    ///
+    /// To also promote `Box<dyn Any + Send>` values of this type, use
+    /// [`register_send`](Self::register_send) instead.
+    pub fn register<T: AnyDebug>(&mut self) {
+        self.types
+            .insert(TypeId::of::<T>(), Registration::new::<T>());
+    }
+
+    /// Registers the type `T`, so that values of this type can be promoted, including
+    /// using [`promote_send`](Self::promote_send).
+    pub fn register_send<T: AnyDebug + Send>(&mut self) {
+        let registration = Registration {
+            boxed_send: Some(|value| value.downcast::<T>().unwrap()),
+            ..Registration::new::<T>()
+        };
+        self.types.insert(TypeId::of::<T>(), registration);
+    }
+
+    /// Returns `true` if `T` has been registered.
+    pub fn is_registered<T: Any>(&self) -> bool {
+        self.types.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Converts `value` into a `Box<dyn AnyDebug>`, if its type has been registered.
+    ///
+    /// ## Errors
+    ///
+    /// If the type of `value` has not been registered, returns `value`.
+    pub fn promote(&self, value: Box<dyn Any>) -> Result<Box<dyn AnyDebug>, Box<dyn Any>> {
+        match self.types.get(&(*value).type_id()) {
+            Some(registration) => Ok((registration.boxed)(value)),
+            None => Err(value),
+        }
+    }
+
+    /// Converts `value` into a `Box<dyn AnyDebug + Send>`, if its type has been registered
+    /// using [`register_send`](Self::register_send).
+    ///
+    /// ## Errors
+    ///
+    /// If the type of `value` has not been registered with `register_send`, returns `value`.
+    pub fn promote_send(
+        &self,
+        value: Box<dyn Any + Send>,
+    ) -> Result<Box<dyn AnyDebug + Send>, Box<dyn Any + Send>> {
+        let boxed_send = self
+            .types
+            .get(&(*value).type_id())
+            .and_then(|registration| registration.boxed_send);
+        match boxed_send {
+            Some(boxed_send) => Ok(boxed_send(value)),
+            None => Err(value),
+        }
+    }
+
+    /// Returns `value` as a `&dyn AnyDebug`, if its type has been registered.
+    pub fn promote_ref<'a>(&self, value: &'a dyn Any) -> Option<&'a dyn AnyDebug> {
+        let registration = self.types.get(&value.type_id())?;
+        (registration.by_ref)(value)
+    }
+}
+
+impl Debug for Promoter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Promoter")
+            .field("types", &RegisteredTypes(&self.types))
+            .finish()
+    }
+}
+
+/// Formats the set of registered types, by name if the `type_name` feature is enabled.
+struct RegisteredTypes<'a>(&'a BTreeMap<TypeId, Registration>);
+
+impl Debug for RegisteredTypes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut set = f.debug_set();
+        #[cfg(feature = "type_name")]
+        for registration in self.0.values() {
+            set.entry(&format_args!("{}", registration.type_name));
+        }
+        #[cfg(not(feature = "type_name"))]
+        for id in self.0.keys() {
+            set.entry(&crate::ShortTypeId::from(*id));
+        }
+        set.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Promoter;
+    use alloc::boxed::Box;
+    use alloc::format;
+    use core::any::Any;
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn promoter_promote() {
+        let mut promoter = Promoter::new();
+        promoter.register::<SomeMessage>();
+        assert!(promoter.is_registered::<SomeMessage>());
+        assert!(!promoter.is_registered::<u32>());
+
+        let val: Box<dyn Any> = Box::new(SomeMessage(220));
+        let val = promoter.promote(val).unwrap();
+        assert_eq!(val.downcast::<SomeMessage>().unwrap().0, 220);
+        let val: Box<dyn Any> = Box::new(221_u32);
+        let val = promoter.promote(val).unwrap_err();
+        assert_eq!(*val.downcast::<u32>().unwrap(), 221);
+    }
+    #[test]
+    fn promoter_promote_send() {
+        let mut promoter = Promoter::new();
+        promoter.register::<u32>();
+        promoter.register_send::<SomeMessage>();
+
+        let val: Box<dyn Any + Send> = Box::new(SomeMessage(222));
+        let val = promoter.promote_send(val).unwrap();
+        assert_eq!(format!("{val:?}"), "SomeMessage(222)");
+        // Types registered without `register_send` can't be promoted to `dyn AnyDebug + Send`.
+        let val: Box<dyn Any + Send> = Box::new(223_u32);
+        assert!(promoter.promote_send(val).is_err());
+    }
+    #[test]
+    fn promoter_promote_ref() {
+        let mut promoter = Promoter::new();
+        promoter.register::<SomeMessage>();
+        let val: &dyn Any = &SomeMessage(224);
+        let val = promoter.promote_ref(val).unwrap();
+        assert_eq!(format!("{val:?}"), "SomeMessage(224)");
+        assert!(promoter.promote_ref(&225_u8).is_none());
+    }
+    #[test]
+    #[cfg(feature = "type_name")]
+    fn promoter_debug() {
+        let mut promoter = Promoter::new();
+        promoter.register::<u8>();
+        assert_eq!(format!("{promoter:?}"), "Promoter { types: {u8} }");
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Requesting values of other types from dynamically typed values.
+
+use core::any::{Any, TypeId};
+use core::fmt::{self, Debug};
+
+use crate::AnyDebug;
+
+/// A value which can provide references to, or values of, types other than its own.
+///
+/// This allows callers to access parts of a value, such as the id of the widget which sent
+/// a message, without knowing its concrete type.
+/// Callers use the `request_ref` and `request_value` methods of `dyn Provider`, which
+/// call [`provide`](Provider::provide) with a [`Request`] for the type they want.
+///
+/// This is modeled on the `Provider` API which was proposed for the standard library.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{Provider, Request};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct WidgetId(u64);
+///
+/// #[derive(Debug)]
+/// struct Click { source: WidgetId, x: f64 }
+///
+/// impl Provider for Click {
+///     fn provide<'a>(&'a self, request: &mut Request<'a>) {
+///         request.provide_ref(&self.source).provide_value(self.x);
+///     }
+/// }
+///
+/// let message: &dyn Provider = &Click { source: WidgetId(4), x: 1.5 };
+/// assert_eq!(message.request_ref::<WidgetId>(), Some(&WidgetId(4)));
+/// assert_eq!(message.request_value::<f64>(), Some(1.5));
+/// assert_eq!(message.request_ref::<u32>(), None);
+/// ```
+pub trait Provider: AnyDebug {
+    /// Provides the values which this value offers to `request`.
+    ///
+    /// Implementations should call the methods of `request` for each type which they can
+    /// provide.
+    /// Only the first value of the requested type is used.
+    fn provide<'a>(&'a self, request: &mut Request<'a>);
+}
+
+/// A request for a value of a single type, passed to [`Provider::provide`].
+pub struct Request<'a> {
+    kind: RequestKind<'a>,
+}
+
+enum RequestKind<'a> {
+    /// A request for a reference to a value of the type with id `wanted`.
+    Ref {
+        wanted: TypeId,
+        found: Option<&'a dyn Any>,
+    },
+    /// A request for a value, stored in an `Option<T>` of the requested type `T`.
+    Value(&'a mut dyn Any),
+}
+
+impl<'a> Request<'a> {
+    /// Provides a reference to `value`, if a reference to a `T` was requested.
+    pub fn provide_ref<T: Any>(&mut self, value: &'a T) -> &mut Self {
+        if let RequestKind::Ref {
+            wanted,
+            found: found @ None,
+        } = &mut self.kind
+        {
+            if *wanted == TypeId::of::<T>() {
+                *found = Some(value);
+            }
+        }
+        self
+    }
+
+    /// Provides `value`, if a value of type `T` was requested.
+    pub fn provide_value<T: Any>(&mut self, value: T) -> &mut Self {
+        self.provide_value_with(|| value)
+    }
+
+    /// Provides the value returned by `f`, if a value of type `T` was requested.
+    ///
+    /// `f` is only called if it is needed.
+    pub fn provide_value_with<T: Any>(&mut self, f: impl FnOnce() -> T) -> &mut Self {
+        if let RequestKind::Value(slot) = &mut self.kind {
+            if let Some(slot @ None) = slot.downcast_mut::<Option<T>>() {
+                *slot = Some(f());
+            }
+        }
+        self
+    }
+}
+
+impl Debug for Request<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Request");
+        match &self.kind {
+            RequestKind::Ref { found, .. } => {
+                debug.field("kind", &"ref").field("found", &found.is_some())
+            }
+            RequestKind::Value(_) => debug.field("kind", &"value"),
+        };
+        debug.finish_non_exhaustive()
+    }
+}
+
+macro_rules! impl_request {
+    ($($ty:ty),+) => {
+        $(
+            impl $ty {
+                /// Returns a reference to a value of type `T` provided by this value, if there
+                /// is one.
+                pub fn request_ref<T: Any>(&self) -> Option<&T> {
+                    let mut request = Request {
+                        kind: RequestKind::Ref {
+                            wanted: TypeId::of::<T>(),
+                            found: None,
+                        },
+                    };
+                    self.provide(&mut request);
+                    match request.kind {
+                        RequestKind::Ref { found, .. } => found?.downcast_ref(),
+                        RequestKind::Value(_) => None,
+                    }
+                }
+
+                /// Returns a value of type `T` provided by this value, if there is one.
+                pub fn request_value<T: Any>(&self) -> Option<T> {
+                    let mut slot: Option<T> = None;
+                    self.provide(&mut Request {
+                        kind: RequestKind::Value(&mut slot),
+                    });
+                    slot
+                }
+            }
+        )+
+    };
+}
+
+impl_request!(
+    dyn Provider,
+    dyn Provider + Send,
+    dyn Provider + Sync,
+    dyn Provider + Send + Sync
+);
+
+crate::impl_downcast!(dyn Provider);
+crate::impl_downcast!(dyn Provider + Send);
+crate::impl_downcast!(dyn Provider + Sync);
+crate::impl_downcast!(dyn Provider + Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::{Provider, Request, RequestKind};
+    use alloc::format;
+    use core::any::TypeId;
+    use core::cell::Cell;
+
+    #[derive(Debug, PartialEq)]
+    struct WidgetId(u32);
+
+    #[derive(Debug)]
+    struct SomeMessage {
+        source: WidgetId,
+        other: WidgetId,
+        values_created: Cell<u32>,
+    }
+
+    impl Provider for SomeMessage {
+        fn provide<'a>(&'a self, request: &mut Request<'a>) {
+            request
+                .provide_ref(&self.source)
+                .provide_ref(&self.other)
+                .provide_value_with(|| {
+                    self.values_created.set(self.values_created.get() + 1);
+                    self.source.0 + 2
+                });
+        }
+    }
+
+    fn message(source: u32) -> SomeMessage {
+        SomeMessage {
+            source: WidgetId(source),
+            other: WidgetId(source + 1),
+            values_created: Cell::new(0),
+        }
+    }
+
+    #[test]
+    fn provider_request_ref() {
+        let val = message(270);
+        let val: &(dyn Provider + Send) = &val;
+        // Only the first reference of the requested type is used.
+        assert_eq!(val.request_ref::<WidgetId>(), Some(&WidgetId(270)));
+        assert_eq!(val.request_ref::<u32>(), None);
+        assert_eq!(val.downcast_ref::<SomeMessage>().unwrap().other.0, 271);
+    }
+    #[test]
+    fn provider_request_value() {
+        let val = message(272);
+        let val: &dyn Provider = &val;
+        assert_eq!(val.request_value::<u32>(), Some(274));
+        assert_eq!(val.request_value::<WidgetId>(), None);
+        // The value is only created when it is requested.
+        assert!(val.request_ref::<WidgetId>().is_some());
+        let created = &val.downcast_ref::<SomeMessage>().unwrap().values_created;
+        assert_eq!(created.get(), 1);
+    }
+    #[test]
+    fn request_debug() {
+        let mut slot: Option<u8> = None;
+        let request = Request {
+            kind: RequestKind::Value(&mut slot),
+        };
+        assert_eq!(format!("{request:?}"), r#"Request { kind: "value", .. }"#);
+        let mut request = Request {
+            kind: RequestKind::Ref {
+                wanted: TypeId::of::<u16>(),
+                found: None,
+            },
+        };
+        request.provide_ref(&275_u16);
+        assert_eq!(
+            format!("{request:?}"),
+            r#"Request { kind: "ref", found: true, .. }"#
+        );
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A global registry of types, for looking up information about types at runtime.
+//!
+//! Applications can [`register`] the types of their messages at startup, then look them up
+//! by [`TypeId`] or by name, or list all of them.
+//! This is useful for debug tooling, which can show all known message types and the fields
+//! of values (see [`debug_fields`]), and for creating values of a type from its name, using
+//! [`register_default`].
+//!
+//! ## Examples
+//!
+//! ```
+//! use anymore::registry;
+//!
+//! #[derive(Clone, Debug, Default)]
+//! struct Refresh;
+//!
+//! registry::register_default::<Refresh>();
+//! registry::register_clone::<Refresh>();
+//!
+//! let info = registry::get_by_name(std::any::type_name::<Refresh>()).unwrap();
+//! let value = info.create_default().unwrap();
+//! assert!(value.is::<Refresh>());
+//! assert!(info.clone_value(&*value).unwrap().is::<Refresh>());
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::fmt::{self, Debug};
+use std::sync::{PoisonError, RwLock};
+
+use crate::{AnyDebug, DebugFields, ShortTypeId};
+
+/// The registered types.
+static REGISTRY: RwLock<BTreeMap<TypeId, TypeInfo>> = RwLock::new(BTreeMap::new());
+
+/// Creates the default value of a registered type.
+type DefaultHook = fn() -> Box<dyn AnyDebug>;
+
+/// Clones a value of a registered type.
+type CloneHook = fn(&dyn AnyDebug) -> Option<Box<dyn AnyDebug>>;
+
+/// Accesses the fields of a value of a registered type.
+type FieldsHook = fn(&dyn AnyDebug) -> Option<&dyn DebugFields>;
+
+/// Information about a registered type.
+///
+/// This is returned by the lookup functions of this module, such as [`get`].
+#[derive(Clone, Copy)]
+pub struct TypeInfo {
+    type_id: TypeId,
+    name: &'static str,
+    default: Option<DefaultHook>,
+    clone: Option<CloneHook>,
+    fields: Option<FieldsHook>,
+}
+
+impl TypeInfo {
+    fn of<T: AnyDebug>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: core::any::type_name::<T>(),
+            default: None,
+            clone: None,
+            fields: None,
+        }
+    }
+
+    /// The [`TypeId`] of the type.
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// The name of the type, as returned by [`core::any::type_name`].
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The [`ShortTypeId`] of the type.
+    pub fn short_id(&self) -> ShortTypeId {
+        ShortTypeId::from(self.type_id)
+    }
+
+    /// Creates the default value of the type, if it was registered using [`register_default`].
+    pub fn create_default(&self) -> Option<Box<dyn AnyDebug>> {
+        self.default.map(|default| default())
+    }
+
+    /// Clones `value`, if the type was registered using [`register_clone`].
+    ///
+    /// Returns `None` if `value` is not of this type.
+    pub fn clone_value(&self, value: &dyn AnyDebug) -> Option<Box<dyn AnyDebug>> {
+        self.clone?(value)
+    }
+
+    /// Returns `value` as a `dyn DebugFields`, if the type was registered using
+    /// [`register_fields`].
+    ///
+    /// Returns `None` if `value` is not of this type.
+    pub fn as_debug_fields<'a>(&self, value: &'a dyn AnyDebug) -> Option<&'a dyn DebugFields> {
+        self.fields?(value)
+    }
+}
+
+impl Debug for TypeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypeInfo")
+            .field("name", &self.name)
+            .field("short_id", &self.short_id())
+            .field("has_default", &self.default.is_some())
+            .field("has_clone", &self.clone.is_some())
+            .field("has_fields", &self.fields.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Adds `T` to the registry, calling `update` with its information.
+///
+/// The hooks of a type which is already registered are kept.
+fn register_with<T: AnyDebug>(update: impl FnOnce(&mut TypeInfo)) {
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    update(
+        registry
+            .entry(TypeId::of::<T>())
+            .or_insert_with(TypeInfo::of::<T>),
+    );
+}
+
+/// Registers the type `T`.
+///
+/// Registering a type which is already registered has no effect.
+pub fn register<T: AnyDebug>() {
+    register_with::<T>(|_| {});
+}
+
+/// Registers the type `T`, so that its default value can be created using
+/// [`TypeInfo::create_default`].
+///
+/// The type doesn't need to be registered using [`register`] first.
+pub fn register_default<T: AnyDebug + Default>() {
+    register_with::<T>(|info| info.default = Some(|| Box::new(T::default())));
+}
+
+/// Registers the type `T`, so that its values can be cloned using [`TypeInfo::clone_value`].
+///
+/// The type doesn't need to be registered using [`register`] first.
+pub fn register_clone<T: AnyDebug + Clone>() {
+    register_with::<T>(|info| {
+        info.clone = Some(|value| {
+            let value: Box<dyn AnyDebug> = Box::new(value.downcast_ref::<T>()?.clone());
+            Some(value)
+        });
+    });
+}
+
+/// Registers the type `T`, so that the fields of its values can be accessed using
+/// [`debug_fields`].
+///
+/// The type doesn't need to be registered using [`register`] first.
+pub fn register_fields<T: DebugFields>() {
+    register_with::<T>(|info| {
+        info.fields = Some(|value| Some(value.downcast_ref::<T>()? as &dyn DebugFields));
+    });
+}
+
+/// Returns `true` if `T` has been registered.
+pub fn is_registered<T: AnyDebug>() -> bool {
+    get(TypeId::of::<T>()).is_some()
+}
+
+/// Returns the information about the type with the given [`TypeId`], if it has been registered.
+pub fn get(type_id: TypeId) -> Option<TypeInfo> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    registry.get(&type_id).copied()
+}
+
+/// Returns the information about the registered type with the given name, as returned by
+/// [`core::any::type_name`].
+///
+/// Type names are not guaranteed to be unique, so if several registered types have the same
+/// name, any one of them is returned.
+pub fn get_by_name(name: &str) -> Option<TypeInfo> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    registry.values().find(|info| info.name == name).copied()
+}
+
+/// Returns `value` as a `dyn DebugFields`, if its type was registered using [`register_fields`].
+///
+/// The same [smart pointer caveat](crate#smart-pointers-and-dyn-anydebug) applies as for
+/// [`AnyDebug::type_name`].
+pub fn debug_fields(value: &dyn AnyDebug) -> Option<&dyn DebugFields> {
+    get(value.type_id())?.as_debug_fields(value)
+}
+
+/// Returns the information about every registered type, sorted by name.
+pub fn types() -> Vec<TypeInfo> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    let mut types: Vec<_> = registry.values().copied().collect();
+    types.sort_by_key(|info| info.name);
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        debug_fields, get, get_by_name, register, register_clone, register_default,
+        register_fields, types,
+    };
+    use crate::{AnyDebug, DebugFields};
+    use alloc::format;
+    use core::any::TypeId;
+
+    // Each test registers its own types, as the registry is shared between tests.
+
+    #[test]
+    fn registry_register() {
+        #[derive(Debug)]
+        struct RegisteredMessage;
+
+        assert!(!super::is_registered::<RegisteredMessage>());
+        register::<RegisteredMessage>();
+        assert!(super::is_registered::<RegisteredMessage>());
+        let info = get(TypeId::of::<RegisteredMessage>()).unwrap();
+        assert!(info.name().ends_with("RegisteredMessage"));
+        assert_eq!(info.type_id(), TypeId::of::<RegisteredMessage>());
+        assert!(info.create_default().is_none());
+        assert!(info.clone_value(&RegisteredMessage).is_none());
+        assert!(types()
+            .iter()
+            .any(|other| other.type_id() == info.type_id()));
+    }
+    #[test]
+    fn registry_hooks() {
+        #[derive(Clone, Debug, Default, PartialEq)]
+        struct HookedMessage(u32);
+
+        register_default::<HookedMessage>();
+        register_clone::<HookedMessage>();
+        // Registering again keeps the hooks.
+        register::<HookedMessage>();
+        let info = get_by_name(core::any::type_name::<HookedMessage>()).unwrap();
+        let val = info.create_default().unwrap();
+        assert_eq!(val.downcast_ref(), Some(&HookedMessage(0)));
+        let val = info.clone_value(&HookedMessage(230)).unwrap();
+        assert_eq!(val.downcast_ref(), Some(&HookedMessage(230)));
+        assert!(info.clone_value(&231_u32).is_none());
+        assert!(info.as_debug_fields(&HookedMessage(232)).is_none());
+        assert_eq!(
+            format!("{info:?}"),
+            format!(
+                "TypeInfo {{ name: {:?}, short_id: {:?}, has_default: true, has_clone: true, has_fields: false, .. }}",
+                info.name(),
+                info.short_id()
+            )
+        );
+    }
+    #[test]
+    fn registry_debug_fields() {
+        #[derive(Debug)]
+        struct FieldsMessage(u32);
+
+        impl DebugFields for FieldsMessage {
+            fn field_count(&self) -> usize {
+                1
+            }
+
+            fn field(&self, index: usize) -> Option<(&'static str, &dyn AnyDebug)> {
+                (index == 0).then_some(("0", &self.0 as &dyn AnyDebug))
+            }
+        }
+
+        let val: &dyn AnyDebug = &FieldsMessage(233);
+        assert!(debug_fields(val).is_none());
+        register_fields::<FieldsMessage>();
+        let fields = debug_fields(val).unwrap();
+        assert_eq!(format!("{:?}", fields.fields()), r#"{"0": 233}"#);
+        assert!(debug_fields(&234_u8).is_none());
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Reference-counted `dyn AnyDebug` values which report the contained value's type.
+
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::fmt::{self, Debug};
+use core::ops::Deref;
+
+use crate::AnyDebug;
+
+/// A shared, thread-safe `dyn AnyDebug` value.
+///
+/// This is a wrapper around `Arc<dyn AnyDebug + Send + Sync>`.
+/// Unlike the `Arc` itself, its [`type_name`](SharedAny::type_name) method reports the
+/// type name of the contained value, rather than that of the smart pointer.
+/// See the [crate level documentation](crate#smart-pointers-and-dyn-anydebug) for details of
+/// the caveat this avoids.
+///
+/// The contained value can be inspected using the methods of `dyn AnyDebug + Send + Sync`,
+/// which this type dereferences to.
+///
+/// For the single-threaded equivalent, see [`LocalShared`].
+#[derive(Clone)]
+pub struct SharedAny(Arc<dyn AnyDebug + Send + Sync>);
+
+impl SharedAny {
+    /// Creates a new `SharedAny` containing `value`.
+    #[track_caller]
+    pub fn new<T: AnyDebug + Send + Sync>(value: T) -> Self {
+        crate::__private::warn_if_nested(&value);
+        Self(Arc::new(value))
+    }
+
+    /// Returns the [`type_name`](core::any::type_name) of the contained value's concrete type.
+    #[cfg(feature = "type_name")]
+    pub fn type_name(&self) -> &'static str {
+        (*self.0).type_name()
+    }
+
+    /// Returns the underlying `Arc`.
+    pub fn into_arc(self) -> Arc<dyn AnyDebug + Send + Sync> {
+        self.0
+    }
+
+    /// Access the actual type of the contained value.
+    ///
+    /// ## Errors
+    ///
+    /// If the value contained within `self` is not of type `T`, returns `self`.
+    pub fn downcast<T: AnyDebug + Send + Sync>(self) -> Result<Arc<T>, Self> {
+        self.0.downcast_arc().map_err(Self)
+    }
+}
+
+impl Deref for SharedAny {
+    type Target = dyn AnyDebug + Send + Sync;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl Debug for SharedAny {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl From<Arc<dyn AnyDebug + Send + Sync>> for SharedAny {
+    fn from(value: Arc<dyn AnyDebug + Send + Sync>) -> Self {
+        Self(value)
+    }
+}
+
+/// A shared, single-threaded `dyn AnyDebug` value.
+///
+/// This is a wrapper around `Rc<dyn AnyDebug>`.
+/// Unlike the `Rc` itself, its [`type_name`](LocalShared::type_name) method reports the
+/// type name of the contained value, rather than that of the smart pointer.
+/// See the [crate level documentation](crate#smart-pointers-and-dyn-anydebug) for details of
+/// the caveat this avoids.
+///
+/// The contained value can be inspected using the methods of `dyn AnyDebug`,
+/// which this type dereferences to.
+///
+/// For the thread-safe equivalent, see [`SharedAny`].
+#[derive(Clone)]
+pub struct LocalShared(Rc<dyn AnyDebug>);
+
+impl LocalShared {
+    /// Creates a new `LocalShared` containing `value`.
+    #[track_caller]
+    pub fn new<T: AnyDebug>(value: T) -> Self {
+        crate::__private::warn_if_nested(&value);
+        Self(Rc::new(value))
+    }
+
+    /// Returns the [`type_name`](core::any::type_name) of the contained value's concrete type.
+    #[cfg(feature = "type_name")]
+    pub fn type_name(&self) -> &'static str {
+        (*self.0).type_name()
+    }
+
+    /// Returns the underlying `Rc`.
+    pub fn into_rc(self) -> Rc<dyn AnyDebug> {
+        self.0
+    }
+
+    /// Access the actual type of the contained value.
+    ///
+    /// ## Errors
+    ///
+    /// If the value contained within `self` is not of type `T`, returns `self`.
+    pub fn downcast<T: AnyDebug>(self) -> Result<Rc<T>, Self> {
+        self.0.downcast_rc().map_err(Self)
+    }
+}
+
+impl Deref for LocalShared {
+    type Target = dyn AnyDebug;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl Debug for LocalShared {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl From<Rc<dyn AnyDebug>> for LocalShared {
+    fn from(value: Rc<dyn AnyDebug>) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalShared, SharedAny};
+    use alloc::format;
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    #[test]
+    #[cfg(feature = "type_name")]
+    fn shared_any_correct_typename() {
+        let val = SharedAny::new(SomeMessage(40));
+        assert!(val.type_name().contains("SomeMessage"));
+        assert!(!val.type_name().contains("Arc"));
+    }
+    #[test]
+    fn shared_any_correct_debug() {
+        let val = SharedAny::new(SomeMessage(41));
+        assert_eq!(format!("{val:?}"), "SomeMessage(41)");
+    }
+    #[test]
+    fn shared_any_downcast_ref() {
+        let val = SharedAny::new(SomeMessage(42));
+        let other = val.clone();
+        assert!(!other.is::<u32>());
+        assert_eq!(other.downcast_ref::<SomeMessage>().unwrap().0, 42);
+    }
+    #[test]
+    fn shared_any_downcast() {
+        let val = SharedAny::new(SomeMessage(43));
+        let val = val.downcast::<u32>().unwrap_err();
+        assert_eq!(val.downcast::<SomeMessage>().unwrap().0, 43);
+    }
+
+    #[test]
+    #[cfg(feature = "type_name")]
+    fn local_shared_correct_typename() {
+        let val = LocalShared::new(SomeMessage(50));
+        assert!(val.type_name().contains("SomeMessage"));
+        assert!(!val.type_name().contains("Rc"));
+    }
+    #[test]
+    fn local_shared_correct_debug() {
+        let val = LocalShared::new(SomeMessage(51));
+        assert_eq!(format!("{val:?}"), "SomeMessage(51)");
+    }
+    #[test]
+    fn local_shared_downcast_ref() {
+        let val = LocalShared::new(SomeMessage(52));
+        let other = val.clone();
+        assert!(!other.is::<u32>());
+        assert_eq!(other.downcast_ref::<SomeMessage>().unwrap().0, 52);
+    }
+    #[test]
+    fn local_shared_downcast() {
+        let val = LocalShared::new(SomeMessage(53));
+        let val = val.downcast::<u32>().unwrap_err();
+        assert_eq!(val.downcast::<SomeMessage>().unwrap().0, 53);
+    }
+}
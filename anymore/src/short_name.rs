@@ -0,0 +1,105 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Type names without module paths.
+
+use core::fmt::{self, Display};
+
+/// Shortens a type name, such as one returned by [`AnyDebug::type_name`](crate::AnyDebug::type_name),
+/// by removing module paths.
+///
+/// The paths are removed from the base type and from all of its generic parameters, so
+/// `my_app::views::ButtonPressed<alloc::string::String>` becomes `ButtonPressed<String>`.
+/// This is useful in logs and debug overlays, where the full names can be very long.
+///
+/// The returned value implements [`Display`], so the name can be shortened without allocating.
+///
+/// Note that the shortened name may be ambiguous, for example if types of the same name are
+/// defined in different modules.
+///
+/// ## Examples
+///
+/// ```
+/// let name = anymore::short_type_name("my_app::views::ButtonPressed<alloc::string::String>");
+/// assert_eq!(name.to_string(), "ButtonPressed<String>");
+/// ```
+pub fn short_type_name(name: &str) -> ShortTypeName<'_> {
+    ShortTypeName(name)
+}
+
+/// A type name displayed without module paths.
+///
+/// This is returned by [`short_type_name`].
+#[derive(Clone, Copy, Debug)]
+pub struct ShortTypeName<'a>(&'a str);
+
+impl Display for ShortTypeName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.0;
+        while !rest.is_empty() {
+            // Each path is made up of identifiers separated by `::`.
+            let path_len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+                .unwrap_or(rest.len());
+            let (path, after) = rest.split_at(path_len);
+            match path.strip_suffix("::") {
+                // A path such as `my_app::f::{{closure}}`, where the last segment isn't an
+                // identifier, keeps the segment before it.
+                Some(parent) => {
+                    f.write_str(last_segment(parent))?;
+                    f.write_str("::")?;
+                }
+                None => f.write_str(last_segment(path))?,
+            }
+            let mut chars = after.chars();
+            if let Some(c) = chars.next() {
+                write!(f, "{c}")?;
+            }
+            rest = chars.as_str();
+        }
+        Ok(())
+    }
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::short_type_name;
+    use alloc::string::ToString;
+
+    #[track_caller]
+    fn assert_short(name: &str, expected: &str) {
+        assert_eq!(short_type_name(name).to_string(), expected);
+    }
+
+    #[test]
+    fn short_type_name_paths() {
+        assert_short("u32", "u32");
+        assert_short("my_app::views::ButtonPressed", "ButtonPressed");
+        assert_short(
+            "alloc::collections::BTreeMap<alloc::string::String, core::option::Option<u8>>",
+            "BTreeMap<String, Option<u8>>",
+        );
+    }
+    #[test]
+    fn short_type_name_punctuation() {
+        assert_short("&'static [my_app::Item; 4]", "&'static [Item; 4]");
+        assert_short(
+            "alloc::boxed::Box<dyn core::fmt::Debug + core::marker::Send>",
+            "Box<dyn Debug + Send>",
+        );
+        assert_short("(my_app::A, my_app::B)", "(A, B)");
+        assert_short("my_app::main::{{closure}}", "main::{{closure}}");
+    }
+    #[test]
+    fn short_type_name_of_value() {
+        struct SomeMessage;
+
+        let name = core::any::type_name::<Option<SomeMessage>>();
+        assert_short(name, "Option<SomeMessage>");
+    }
+}
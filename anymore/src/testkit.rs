@@ -0,0 +1,383 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Conformance checks for downstream implementations of Anymore's traits.
+//!
+//! These functions are intended to be called from the tests of crates which implement
+//! [`Payload`] for their own smart pointers or wrappers, or which provide their own stores
+//! or dispatchers of dynamically typed values.
+//! Stores and dispatchers are checked through the [`StoreUnderTest`] and
+//! [`DispatcherUnderTest`] traits, which adapt them to a common interface.
+//! They panic with a description of the problem if the implementation doesn't uphold the
+//! expectations of Anymore.
+//!
+//! ## Examples
+//!
+//! ```
+//! use anymore::{AnyDebug, Payload};
+//! use anymore::testkit::{check_debug_forwarding, check_payload};
+//!
+//! /// A handle to a dynamically typed value.
+//! #[derive(Debug)]
+//! struct Handle(Box<dyn AnyDebug>);
+//!
+//! impl Payload for Handle {
+//!     fn as_any_debug(&self) -> &dyn AnyDebug {
+//!         &*self.0
+//!     }
+//! }
+//!
+//! check_payload(|probe| Handle(Box::new(probe)));
+//! ```
+//!
+//! `Handle`'s derived `Debug` implementation includes the name of the wrapper, so doesn't
+//! forward to the contained value:
+//!
+//! ```should_panic
+//! # use anymore::{AnyDebug, Payload};
+//! # use anymore::testkit::check_debug_forwarding;
+//! # #[derive(Debug)]
+//! # struct Handle(Box<dyn AnyDebug>);
+//! # impl Payload for Handle {
+//! #     fn as_any_debug(&self) -> &dyn AnyDebug {
+//! #         &*self.0
+//! #     }
+//! # }
+//! check_debug_forwarding(|probe| Handle(Box::new(probe)));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::Debug;
+
+use crate::{AnyDebug, Payload};
+
+/// A value used by the checks in this module to test that the original value is accessible.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Probe(pub u64);
+
+/// The probes which each check is run with.
+const PROBES: [Probe; 3] = [Probe(0), Probe(1), Probe(u64::MAX)];
+
+/// Checks that the values created by `wrap` implement [`Payload`] correctly.
+///
+/// `wrap` should store the given [`Probe`] in the payload type being tested.
+/// This checks that [`as_any_debug`](Payload::as_any_debug) returns the probe itself, rather
+/// than the wrapper around it, and that the provided methods downcast to the probe.
+///
+/// ## Panics
+///
+/// If the payload doesn't give access to the original probe.
+#[track_caller]
+pub fn check_payload<P: Payload>(wrap: impl Fn(Probe) -> P) {
+    for probe in PROBES {
+        let payload = wrap(probe);
+        let inner = payload.as_any_debug();
+        assert!(
+            inner.is::<Probe>(),
+            "`as_any_debug` should return the wrapped `Probe`, but returned {inner:?}"
+        );
+        assert_eq!(
+            payload.downcast_ref::<Probe>(),
+            Some(&probe),
+            "`downcast_ref` should return the wrapped `Probe`"
+        );
+        assert!(
+            !payload.is::<u64>(),
+            "`is` should only return `true` for the type of the wrapped value"
+        );
+        assert_eq!(
+            format!("{inner:?}"),
+            format!("{probe:?}"),
+            "`as_any_debug` should give the `Debug` output of the wrapped `Probe`"
+        );
+    }
+}
+
+/// Checks that the `Debug` implementation of the values created by `wrap` forwards to the
+/// wrapped value.
+///
+/// Wrappers around dynamically typed values, such as [`SharedAny`](crate::SharedAny), are
+/// expected to print only the value they contain, so that they can be substituted for the
+/// value in diagnostics.
+///
+/// ## Panics
+///
+/// If the `Debug` output of the wrapper differs from that of the wrapped [`Probe`].
+#[track_caller]
+pub fn check_debug_forwarding<P: Debug>(wrap: impl Fn(Probe) -> P) {
+    for probe in PROBES {
+        let wrapper = wrap(probe);
+        assert_eq!(
+            format!("{wrapper:?}"),
+            format!("{probe:?}"),
+            "the `Debug` output of the wrapper should be that of the wrapped `Probe`"
+        );
+        assert_eq!(
+            format!("{wrapper:#?}"),
+            format!("{probe:#?}"),
+            "the alternate `Debug` output of the wrapper should be that of the wrapped `Probe`"
+        );
+    }
+}
+
+/// Checks that the values created by `wrap` implement [`Payload`] correctly, and are `Send`.
+///
+/// This is [`check_payload`], for payload types which are intended to preserve the `Send`
+/// bound of `dyn AnyDebug + Send`.
+/// The bound is checked at compile time:
+///
+/// ```compile_fail
+/// use std::rc::Rc;
+/// use anymore::AnyDebug;
+///
+/// anymore::testkit::check_send_payload(|probe| Rc::new(probe) as Rc<dyn AnyDebug>);
+/// ```
+///
+/// ## Panics
+///
+/// If the payload doesn't give access to the original probe.
+#[track_caller]
+pub fn check_send_payload<P: Payload + Send>(wrap: impl Fn(Probe) -> P) {
+    check_payload(wrap);
+}
+
+/// Checks that the values created by `wrap` implement [`Payload`] correctly, and are `Send`
+/// and `Sync`.
+///
+/// This is [`check_payload`], for payload types which are intended to preserve the bounds
+/// of `dyn AnyDebug + Send + Sync`.
+/// The bounds are checked at compile time.
+///
+/// ## Panics
+///
+/// If the payload doesn't give access to the original probe.
+#[track_caller]
+pub fn check_send_sync_payload<P: Payload + Send + Sync>(wrap: impl Fn(Probe) -> P) {
+    check_payload(wrap);
+}
+
+/// A store of dynamically typed values, keyed by their type, which can be checked by
+/// [`check_store`].
+///
+/// Implement this for a store (or a wrapper around it) by forwarding to its methods.
+pub trait StoreUnderTest: Debug {
+    /// Inserts `value`, replacing any value of the same type.
+    fn insert<T: AnyDebug + Clone + Send + Sync>(&mut self, value: T);
+
+    /// Returns a copy of the value of type `T`, if there is one.
+    fn get<T: AnyDebug + Clone + Send + Sync>(&self) -> Option<T>;
+
+    /// Removes and returns the value of type `T`, if there is one.
+    fn remove<T: AnyDebug + Clone + Send + Sync>(&mut self) -> Option<T>;
+}
+
+/// Checks that the stores created by `new` keep values of different types separate, and
+/// return the values which were inserted.
+///
+/// `new` should create an empty store.
+/// This also checks that the `Debug` output of the store includes that of its values.
+///
+/// ## Panics
+///
+/// If the store doesn't return the inserted values.
+#[track_caller]
+pub fn check_store<S: StoreUnderTest>(new: impl Fn() -> S) {
+    for probe in PROBES {
+        let mut store = new();
+        assert!(
+            store.get::<Probe>().is_none(),
+            "a new store should not contain a `Probe`"
+        );
+        store.insert(probe);
+        // A value of a different type, with the same contents.
+        store.insert(probe.0);
+        assert_eq!(
+            store.get::<Probe>(),
+            Some(probe),
+            "`get` should return the inserted `Probe`"
+        );
+        assert_eq!(
+            store.get::<u64>(),
+            Some(probe.0),
+            "values of different types should be stored separately"
+        );
+        let debug = format!("{store:?}");
+        assert!(
+            debug.contains(&format!("{probe:?}")),
+            "the `Debug` output of the store should include that of its values, but was {debug}"
+        );
+        assert_eq!(
+            store.remove::<Probe>(),
+            Some(probe),
+            "`remove` should return the inserted `Probe`"
+        );
+        assert!(
+            store.get::<Probe>().is_none(),
+            "`remove` should remove the `Probe` from the store"
+        );
+        assert_eq!(
+            store.get::<u64>(),
+            Some(probe.0),
+            "`remove` should only remove the value of the requested type"
+        );
+    }
+}
+
+/// A dispatcher which calls a handler for each type of dynamically typed message, which can be
+/// checked by [`check_dispatcher`].
+///
+/// Implement this for a dispatcher (or a wrapper around it) by forwarding to its methods.
+pub trait DispatcherUnderTest {
+    /// Registers `handler` to be called with messages of type `T`.
+    fn register<T: AnyDebug>(&mut self, handler: impl FnMut(T) + 'static);
+
+    /// Calls the handler registered for the type of `message`.
+    ///
+    /// ## Errors
+    ///
+    /// If there is no handler for the type of `message`, returns `message`.
+    fn dispatch(&mut self, message: Box<dyn AnyDebug>) -> Result<(), Box<dyn AnyDebug>>;
+}
+
+/// Checks that the dispatchers created by `new` call the handler for the type of each
+/// message, and return the messages for which there is no handler.
+///
+/// `new` should create a dispatcher with no handlers.
+///
+/// ## Panics
+///
+/// If a message isn't passed to the correct handler, or an unhandled message is lost.
+#[track_caller]
+pub fn check_dispatcher<D: DispatcherUnderTest>(new: impl Fn() -> D) {
+    for probe in PROBES {
+        let mut dispatcher = new();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&received);
+        dispatcher.register(move |probe: Probe| sink.borrow_mut().push(probe));
+        assert!(
+            dispatcher.dispatch(Box::new(probe)).is_ok(),
+            "a `Probe` should be dispatched to the handler registered for `Probe`"
+        );
+        assert_eq!(
+            *received.borrow(),
+            [probe],
+            "the handler should be called once, with the dispatched `Probe`"
+        );
+        let Err(unhandled) = dispatcher.dispatch(Box::new(probe.0)) else {
+            panic!("a message with no handler should be returned from `dispatch`");
+        };
+        assert_eq!(
+            unhandled.downcast_ref::<u64>(),
+            Some(&probe.0),
+            "the unhandled message should be returned unchanged"
+        );
+        assert_eq!(
+            received.borrow().len(),
+            1,
+            "the handler for `Probe` should not be called for messages of other types"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_debug_forwarding, check_dispatcher, check_payload, check_send_sync_payload,
+        check_store, DispatcherUnderTest, StoreUnderTest,
+    };
+    use crate::dispatch::HandlerRegistry;
+    use crate::map::AnyMap;
+    use crate::{AnyDebug, LocalShared, SharedAny};
+    use alloc::boxed::Box;
+    use alloc::rc::Rc;
+    use alloc::sync::Arc;
+
+    impl StoreUnderTest for AnyMap {
+        fn insert<T: AnyDebug + Clone + Send + Sync>(&mut self, value: T) {
+            Self::insert(self, value);
+        }
+        fn get<T: AnyDebug + Clone + Send + Sync>(&self) -> Option<T> {
+            Self::get::<T>(self).cloned()
+        }
+        fn remove<T: AnyDebug + Clone + Send + Sync>(&mut self) -> Option<T> {
+            Self::remove::<T>(self)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl StoreUnderTest for crate::map::ConcurrentAnyMap {
+        fn insert<T: AnyDebug + Clone + Send + Sync>(&mut self, value: T) {
+            Self::insert(self, value);
+        }
+        fn get<T: AnyDebug + Clone + Send + Sync>(&self) -> Option<T> {
+            self.with(T::clone)
+        }
+        fn remove<T: AnyDebug + Clone + Send + Sync>(&mut self) -> Option<T> {
+            Self::remove::<T>(self)
+        }
+    }
+
+    impl DispatcherUnderTest for HandlerRegistry {
+        fn register<T: AnyDebug>(&mut self, mut handler: impl FnMut(T) + 'static) {
+            Self::register(self, move |message: T, _: &mut ()| handler(message));
+        }
+        fn dispatch(&mut self, message: Box<dyn AnyDebug>) -> Result<(), Box<dyn AnyDebug>> {
+            Self::dispatch(self, message, &mut ()).map_err(|error| error.into_message())
+        }
+    }
+
+    /// A dispatcher which never calls its handlers.
+    struct DropsMessages;
+
+    impl DispatcherUnderTest for DropsMessages {
+        fn register<T: AnyDebug>(&mut self, _: impl FnMut(T) + 'static) {}
+        fn dispatch(&mut self, _: Box<dyn AnyDebug>) -> Result<(), Box<dyn AnyDebug>> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn crate_payloads_conform() {
+        check_payload(|probe| Box::new(probe) as Box<dyn AnyDebug>);
+        check_payload(|probe| Box::new(probe) as Box<dyn AnyDebug + Send + Sync>);
+        check_payload(|probe| Rc::new(probe) as Rc<dyn AnyDebug>);
+        check_payload(|probe| Arc::new(probe) as Arc<dyn AnyDebug + Send + Sync>);
+        check_payload(SharedAny::new);
+        check_payload(LocalShared::new);
+        check_payload(crate::message::Message::new);
+        check_send_sync_payload(SharedAny::new);
+    }
+    #[test]
+    fn crate_stores_conform() {
+        check_store(AnyMap::new);
+        #[cfg(feature = "std")]
+        check_store(crate::map::ConcurrentAnyMap::new);
+    }
+    #[test]
+    fn crate_dispatchers_conform() {
+        check_dispatcher(HandlerRegistry::new);
+    }
+    #[test]
+    #[should_panic(expected = "should be called once")]
+    fn dropped_message_fails() {
+        check_dispatcher(|| DropsMessages);
+    }
+    #[test]
+    fn crate_wrappers_forward_debug() {
+        check_debug_forwarding(|probe| Box::new(probe) as Box<dyn AnyDebug>);
+        check_debug_forwarding(SharedAny::new);
+        check_debug_forwarding(LocalShared::new);
+    }
+    #[test]
+    #[should_panic(expected = "should return the wrapped `Probe`")]
+    fn nested_box_fails() {
+        check_payload(|probe| {
+            let inner: Box<dyn AnyDebug> = Box::new(probe);
+            Box::new(inner) as Box<dyn AnyDebug>
+        });
+    }
+}
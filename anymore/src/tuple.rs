@@ -0,0 +1,141 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Downcasting lists of `dyn AnyDebug` values to tuples.
+
+use crate::{AnyDebug, ArgumentError, Payload};
+
+/// A tuple of types which a list of `dyn AnyDebug` values can be downcast to.
+///
+/// This is implemented for tuples of up to eight [`AnyDebug`] types.
+/// See [`downcast_tuple`] for details.
+pub trait DowncastTuple {
+    /// The tuple of references to each of the types.
+    type Refs<'a>;
+
+    /// Downcasts each of `values` to the corresponding type of this tuple.
+    ///
+    /// ## Errors
+    ///
+    /// If there are not the same number of values as types in the tuple, or if any of the
+    /// values is of the wrong type.
+    fn downcast_tuple<P: Payload>(values: &[P]) -> Result<Self::Refs<'_>, ArgumentError>;
+}
+
+/// Downcasts a list of `dyn AnyDebug` values to a tuple of references to their expected types.
+///
+/// This is useful for extracting the arguments of commands which are passed as a list
+/// of dynamically typed values.
+/// The values can be held in any way which implements [`Payload`], such as
+/// `Box<dyn AnyDebug>` or `&dyn AnyDebug`.
+///
+/// ## Errors
+///
+/// If the number of values doesn't match the number of types in `T`, or if any of the values
+/// is of the wrong type.
+/// The error names the position of the first value which was of the wrong type.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::AnyDebug;
+///
+/// #[derive(Debug)]
+/// struct Point { x: f64, y: f64 }
+///
+/// let args: [&dyn AnyDebug; 2] = [&"move", &Point { x: 1.0, y: 2.0 }];
+/// let (name, point) = anymore::downcast_tuple::<(&str, Point)>(&args).unwrap();
+/// assert_eq!(*name, "move");
+/// assert_eq!(point.x, 1.0);
+/// ```
+pub fn downcast_tuple<T: DowncastTuple>(
+    values: &[impl Payload],
+) -> Result<T::Refs<'_>, ArgumentError> {
+    T::downcast_tuple(values)
+}
+
+fn downcast_at<T: AnyDebug>(values: &[impl Payload], position: usize) -> Result<&T, ArgumentError> {
+    values[position]
+        .as_any_debug()
+        .try_downcast_ref::<T>()
+        .map_err(|mismatch| ArgumentError::Type { position, mismatch })
+}
+
+macro_rules! impl_downcast_tuple {
+    ($count:literal; $($name:ident $position:literal),+) => {
+        impl<$($name: AnyDebug),+> DowncastTuple for ($($name,)+) {
+            type Refs<'a> = ($(&'a $name,)+);
+
+            fn downcast_tuple<P: Payload>(values: &[P]) -> Result<Self::Refs<'_>, ArgumentError> {
+                if values.len() != $count {
+                    return Err(ArgumentError::Count {
+                        expected: $count,
+                        actual: values.len(),
+                    });
+                }
+                Ok(($(downcast_at::<$name>(values, $position)?,)+))
+            }
+        }
+    };
+}
+
+impl_downcast_tuple!(1; A 0);
+impl_downcast_tuple!(2; A 0, B 1);
+impl_downcast_tuple!(3; A 0, B 1, C 2);
+impl_downcast_tuple!(4; A 0, B 1, C 2, D 3);
+impl_downcast_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_downcast_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_downcast_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_downcast_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+#[cfg(test)]
+mod tests {
+    use super::downcast_tuple;
+    use crate::{AnyDebug, ArgumentError};
+
+    #[derive(Debug)]
+    struct SomeMessage(u32);
+
+    #[test]
+    fn downcast_tuple_success() {
+        let args: [&dyn AnyDebug; 3] = [&SomeMessage(150), &151_u32, &"152"];
+        let (message, number, text) = downcast_tuple::<(SomeMessage, u32, &str)>(&args).unwrap();
+        assert_eq!(message.0, 150);
+        assert_eq!(*number, 151);
+        assert_eq!(*text, "152");
+    }
+    #[test]
+    fn downcast_tuple_wrong_type() {
+        let args: [&dyn AnyDebug; 2] = [&SomeMessage(153), &SomeMessage(154)];
+        let err = downcast_tuple::<(SomeMessage, u32)>(&args).unwrap_err();
+        let ArgumentError::Type { position, mismatch } = err else {
+            panic!("Expected a type mismatch, got {err:?}");
+        };
+        assert_eq!(position, 1);
+        assert!(mismatch.actual().ends_with("SomeMessage"));
+    }
+    #[test]
+    fn downcast_tuple_wrong_count() {
+        let args: [&dyn AnyDebug; 1] = [&SomeMessage(155)];
+        let err = downcast_tuple::<(SomeMessage, u32)>(&args).unwrap_err();
+        assert_eq!(
+            err,
+            ArgumentError::Count {
+                expected: 2,
+                actual: 1
+            }
+        );
+    }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn downcast_tuple_boxes() {
+        use alloc::boxed::Box;
+        use alloc::vec;
+
+        let args: alloc::vec::Vec<Box<dyn AnyDebug + Send>> =
+            vec![Box::new(156_u8), Box::new(SomeMessage(157))];
+        let (number, message) = downcast_tuple::<(u8, SomeMessage)>(&args).unwrap();
+        assert_eq!(*number, 156);
+        assert_eq!(message.0, 157);
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Short textual identifiers for [`TypeId`]s.
+
+use core::any::TypeId;
+use core::fmt::{self, Debug, Display};
+use core::hash::{Hash, Hasher};
+
+use crate::AnyDebug;
+
+/// A short identifier for a [`TypeId`], for use in diagnostics.
+///
+/// The [`Debug`] output of a `TypeId` is long, and its format is not guaranteed.
+/// This type instead displays as a short hexadecimal form such as `#a3f9c2`, which
+/// can be used to correlate log lines involving the same type even when its name
+/// isn't available.
+///
+/// The identifier is derived from a hash of the `TypeId`, so is the same for a given type
+/// for the whole run of a program (and between runs of the same binary).
+/// However, `TypeId`s are not stable between compilations, so neither is this identifier.
+/// Distinct types may also very occasionally share an identifier.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::{AnyDebug, ShortTypeId};
+///
+/// #[derive(Debug)]
+/// struct Click;
+///
+/// let message: &dyn AnyDebug = &Click;
+/// assert_eq!(ShortTypeId::of_val(message), ShortTypeId::of::<Click>());
+/// println!("Unexpected message of type {}", ShortTypeId::of_val(message));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShortTypeId(u32);
+
+impl ShortTypeId {
+    /// The number of bits of the hash which are kept.
+    const BITS: u32 = 24;
+
+    /// Returns the identifier of the type `T`.
+    pub fn of<T: ?Sized + 'static>() -> Self {
+        Self::from(TypeId::of::<T>())
+    }
+
+    /// Returns the identifier of the concrete type of `value`.
+    ///
+    /// The same [smart pointer caveat](crate#smart-pointers-and-dyn-anydebug) applies
+    /// as for [`AnyDebug::type_name`].
+    pub fn of_val(value: &dyn AnyDebug) -> Self {
+        Self::from(value.type_id())
+    }
+
+    /// Returns the numeric value of this identifier.
+    pub fn to_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<TypeId> for ShortTypeId {
+    fn from(id: TypeId) -> Self {
+        let mut hasher = Fnv1a::default();
+        id.hash(&mut hasher);
+        let hash = hasher.finish();
+        // Fold the whole hash into the kept bits, rather than discarding the upper bits.
+        let folded = (hash ^ (hash >> 24) ^ (hash >> 48)) & ((1 << Self::BITS) - 1);
+        Self(folded as u32)
+    }
+}
+
+impl Display for ShortTypeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:06x}", self.0)
+    }
+}
+
+impl Debug for ShortTypeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShortTypeId({self})")
+    }
+}
+
+/// The 64-bit FNV-1a hash function.
+///
+/// This is used because it is simple and deterministic; `core` doesn't provide a
+/// hasher, and the hasher in `std` is randomly seeded.
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::ShortTypeId;
+    use crate::AnyDebug;
+    use alloc::format;
+
+    #[derive(Debug)]
+    struct SomeMessage;
+
+    #[test]
+    fn short_type_id_of_val() {
+        let val: &dyn AnyDebug = &SomeMessage;
+        assert_eq!(ShortTypeId::of_val(val), ShortTypeId::of::<SomeMessage>());
+        assert_ne!(ShortTypeId::of_val(val), ShortTypeId::of::<u32>());
+    }
+    #[test]
+    fn short_type_id_display() {
+        let text = format!("{}", ShortTypeId::of::<SomeMessage>());
+        assert_eq!(text.len(), 7);
+        assert!(text.starts_with('#'));
+        assert!(text[1..].chars().all(|c| c.is_ascii_hexdigit()));
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! The derive macros for Anymore's `DebugAny` and `DebugFields`.
+//!
+//! This crate should not be used directly; enable the `derive` feature of
+//! [Anymore](https://docs.rs/anymore) instead.
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+/// Derives [`Debug`] for a struct, rendering fields which don't implement `Debug` as the
+/// name of their type.
+///
+/// This allows structs which contain values of third-party types without a `Debug`
+/// implementation to be used as `AnyDebug` values.
+///
+/// Only structs without generic parameters are supported, as whether a field's type
+/// implements `Debug` must be known where the struct is defined.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::DebugAny;
+///
+/// struct Texture;
+///
+/// #[derive(DebugAny)]
+/// struct TextureLoaded {
+///     id: u32,
+///     texture: Texture,
+/// }
+///
+/// let message = TextureLoaded { id: 3, texture: Texture };
+/// assert!(format!("{message:?}").starts_with("TextureLoaded { id: 3, texture: "));
+/// assert!(format!("{message:?}").ends_with("Texture }"));
+/// ```
+#[proc_macro_derive(DebugAny)]
+pub fn derive_debug_any(input: TokenStream) -> TokenStream {
+    finish(parse(input, "DebugAny").and_then(|(name, fields)| expand_debug_any(&name, fields)))
+}
+
+/// Derives `DebugFields` for a struct, giving access to each of its fields by name.
+///
+/// The fields of tuple structs are named by their index, such as `"0"`.
+/// Each field must implement `AnyDebug`, and the struct must implement `Debug`.
+///
+/// As with `DebugAny`, only structs without generic parameters are supported.
+///
+/// ## Examples
+///
+/// ```
+/// use anymore::DebugFields;
+///
+/// #[derive(Debug, DebugFields)]
+/// struct Resize {
+///     width: u32,
+///     height: u32,
+/// }
+///
+/// let message: &dyn DebugFields = &Resize { width: 640, height: 480 };
+/// let names: Vec<_> = message.fields().map(|(name, _)| name).collect();
+/// assert_eq!(names, ["width", "height"]);
+/// ```
+#[proc_macro_derive(DebugFields)]
+pub fn derive_debug_fields(input: TokenStream) -> TokenStream {
+    finish(
+        parse(input, "DebugFields").and_then(|(name, fields)| expand_debug_fields(&name, fields)),
+    )
+}
+
+/// Returns the output of a derive macro, or a compile error with the message from `result`.
+fn finish(result: Result<TokenStream, String>) -> TokenStream {
+    match result {
+        Ok(output) => output,
+        Err(message) => format!("::core::compile_error!({message:?});")
+            .parse()
+            .unwrap(),
+    }
+}
+
+/// The fields of a struct.
+enum Fields {
+    Named(Vec<String>),
+    Unnamed(usize),
+    Unit,
+}
+
+/// Returns the name and fields of the struct which `derive` is applied to.
+fn parse(input: TokenStream, derive: &str) -> Result<(String, Fields), String> {
+    let mut tokens = input.into_iter().peekable();
+    let name = loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => match tokens.next() {
+                Some(TokenTree::Ident(name)) => break name.to_string(),
+                _ => return Err("expected the name of the struct".into()),
+            },
+            Some(TokenTree::Ident(ident))
+                if ident.to_string() == "enum" || ident.to_string() == "union" =>
+            {
+                return Err(format!("`{derive}` can only be derived for structs"));
+            }
+            Some(_) => {}
+            None => return Err("expected a struct".into()),
+        }
+    };
+    let fields = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            Fields::Named(named_fields(group.stream()))
+        }
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            Fields::Unnamed(split_fields(group.stream()).len())
+        }
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ';' => Fields::Unit,
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            return Err(format!("`{derive}` can't be derived for generic structs"));
+        }
+        _ => {
+            return Err(format!(
+                "`{derive}` can't be derived for structs with `where` clauses"
+            ))
+        }
+    };
+    Ok((name, fields))
+}
+
+fn expand_debug_any(name: &str, fields: Fields) -> Result<TokenStream, String> {
+    let body = match fields {
+        Fields::Named(names) => {
+            let mut body = format!("f.debug_struct({name:?})");
+            for field in names {
+                let label = field.strip_prefix("r#").unwrap_or(&field);
+                body += &format!(".field({label:?}, {})", field_value(&field));
+            }
+            body + ".finish()"
+        }
+        Fields::Unnamed(count) => {
+            let mut body = format!("f.debug_tuple({name:?})");
+            for index in 0..count {
+                body += &format!(".field({})", field_value(&index.to_string()));
+            }
+            body + ".finish()"
+        }
+        Fields::Unit => format!("f.write_str({name:?})"),
+    };
+    // The traits which choose how each field is formatted must be in scope, but importing
+    // them when there are no fields would cause an unused import warning.
+    let imports = if body.contains("maybe_debug") {
+        "use ::anymore::__private::debug_any::*;"
+    } else {
+        ""
+    };
+    let output = format!(
+        "#[automatically_derived]
+        impl ::core::fmt::Debug for {name} {{
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+                {imports}
+                {body}
+            }}
+        }}"
+    );
+    output
+        .parse()
+        .map_err(|_| "failed to generate `Debug` impl".into())
+}
+
+fn expand_debug_fields(name: &str, fields: Fields) -> Result<TokenStream, String> {
+    let names = match fields {
+        Fields::Named(names) => names,
+        Fields::Unnamed(count) => (0..count).map(|index| index.to_string()).collect(),
+        Fields::Unit => vec![],
+    };
+    let count = names.len();
+    let mut arms = String::new();
+    for (index, field) in names.iter().enumerate() {
+        let label = field.strip_prefix("r#").unwrap_or(field);
+        arms += &format!(
+            "{index} => ::core::option::Option::Some(
+                ({label:?}, &self.{field} as &dyn ::anymore::AnyDebug),
+            ),"
+        );
+    }
+    let output = format!(
+        "#[automatically_derived]
+        impl ::anymore::DebugFields for {name} {{
+            fn field_count(&self) -> usize {{
+                {count}
+            }}
+
+            fn field(
+                &self,
+                index: usize,
+            ) -> ::core::option::Option<(&'static str, &dyn ::anymore::AnyDebug)> {{
+                match index {{
+                    {arms}
+                    _ => ::core::option::Option::None,
+                }}
+            }}
+        }}"
+    );
+    output
+        .parse()
+        .map_err(|_| "failed to generate `DebugFields` impl".into())
+}
+
+/// The expression which debug formats the field of `self` named `field`.
+fn field_value(field: &str) -> String {
+    format!("&(&&::anymore::__private::debug_any::MaybeDebug(&self.{field})).maybe_debug()")
+}
+
+/// Returns the name of each of the named fields in `stream`.
+fn named_fields(stream: TokenStream) -> Vec<String> {
+    split_fields(stream)
+        .into_iter()
+        .filter_map(|field| {
+            let mut tokens = field.into_iter();
+            while let Some(token) = tokens.next() {
+                match token {
+                    // Skip the attributes of the field.
+                    TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                        tokens.next();
+                    }
+                    TokenTree::Ident(ident) if ident.to_string() == "pub" => {}
+                    // The visibility restriction, as in `pub(crate)`.
+                    TokenTree::Group(_) => {}
+                    TokenTree::Ident(ident) => return Some(ident.to_string()),
+                    _ => {}
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+/// Splits the fields in `stream` at the commas between them.
+fn split_fields(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut fields = vec![];
+    let mut current = vec![];
+    // Commas inside generic arguments, such as in `HashMap<K, V>`, don't separate fields.
+    let mut angle_depth = 0_usize;
+    let mut previous_joint_dash = false;
+    for token in stream {
+        let mut joint_dash = false;
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                ',' if angle_depth == 0 => {
+                    fields.push(core::mem::take(&mut current));
+                    continue;
+                }
+                '<' => angle_depth += 1,
+                // The `>` of `->` in function pointer types doesn't close generic arguments.
+                '>' if !previous_joint_dash => angle_depth = angle_depth.saturating_sub(1),
+                '-' => joint_dash = punct.spacing() == Spacing::Joint,
+                _ => {}
+            }
+        }
+        previous_joint_dash = joint_dash;
+        current.push(token);
+    }
+    if !current.is_empty() {
+        fields.push(current);
+    }
+    fields
+}
@@ -0,0 +1,105 @@
+// Copyright 2025 the Anymore Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of the output of `#[derive(DebugAny)]` and `#[derive(DebugFields)]`.
+
+#![expect(
+    dead_code,
+    reason = "The fields are only read by the derived `Debug` implementations"
+)]
+
+use std::collections::HashMap;
+
+use anymore::{AnyDebug, DebugAny, DebugFields, DebugFieldsIter};
+
+struct NotDebug;
+
+#[derive(DebugAny)]
+struct Named {
+    /// A documented field.
+    pub id: u32,
+    pub(crate) opaque: NotDebug,
+    map: HashMap<u8, NotDebug>,
+    callback: fn(u8) -> u8,
+    r#type: &'static str,
+}
+
+#[derive(DebugAny)]
+struct Tuple(u32, NotDebug);
+
+#[derive(DebugAny)]
+struct Unit;
+
+#[test]
+fn named_fields() {
+    let value = Named {
+        id: 180,
+        opaque: NotDebug,
+        map: HashMap::new(),
+        callback: |x| x,
+        r#type: "named",
+    };
+    let text = format!("{value:?}");
+    assert!(text.starts_with("Named { id: 180, opaque: "), "{text}");
+    assert!(
+        text.contains("::NotDebug, map: std::collections::"),
+        "{text}"
+    );
+    assert!(text.ends_with(", type: \"named\" }"), "{text}");
+    assert_eq!((value.callback)(1), 1);
+}
+
+#[test]
+fn tuple_fields() {
+    let value: &dyn AnyDebug = &Tuple(181, NotDebug);
+    let text = format!("{value:?}");
+    assert!(text.starts_with("Tuple(181, "), "{text}");
+    assert!(text.ends_with("::NotDebug)"), "{text}");
+}
+
+#[test]
+fn unit() {
+    assert_eq!(format!("{Unit:?}"), "Unit");
+}
+
+#[derive(Debug, DebugFields)]
+struct NamedFields {
+    id: u32,
+    r#type: &'static str,
+}
+
+#[derive(Debug, DebugFields)]
+struct TupleFields(u32, String);
+
+#[derive(Debug, DebugFields)]
+struct UnitFields;
+
+#[test]
+fn debug_fields_named() {
+    let value = NamedFields {
+        id: 182,
+        r#type: "named",
+    };
+    let fields: Vec<_> = DebugFieldsIter::new(&value).collect();
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].0, "id");
+    assert_eq!(fields[0].1.downcast_ref::<u32>(), Some(&182));
+    assert_eq!(fields[1].0, "type");
+    assert_eq!(format!("{:?}", fields[1].1), r#""named""#);
+}
+
+#[test]
+fn debug_fields_tuple() {
+    let value: &dyn DebugFields = &TupleFields(183, "text".into());
+    assert_eq!(
+        format!("{:?}", value.fields()),
+        r#"{"0": 183, "1": "text"}"#
+    );
+    assert!(value.field(2).is_none());
+}
+
+#[test]
+fn debug_fields_unit() {
+    assert_eq!(UnitFields.field_count(), 0);
+    assert!(DebugFieldsIter::new(&UnitFields).next().is_none());
+}